@@ -0,0 +1,1162 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::collision::Geometry;
+use crate::input::{KeyState, KEYS_DEFAULT};
+use crate::player::{Ground, Kinematics, PlayerState, Stance, STEP_SIZE};
+use crate::theory;
+
+use cgmath::prelude::*;
+use rand::prelude::*;
+
+use cgmath::{Deg, Rad, Vector2, Vector3};
+use rand::rngs::StdRng;
+
+const MAX_TURN_RATE: Deg<f32> = Deg(250.0);
+const START_DELAY_S: f32 = 1.0;
+
+// obstacles are looked for along the path of the next hop or so, from step
+// height, as steps are climbed anyway
+const LOOK_AHEAD_S: f32 = 1.5;
+const LOOK_AHEAD_AIR_S: f32 = 0.5;
+const LOOK_AHEAD_MIN: f32 = 512.0;
+const LOOK_AHEAD_STEPS: usize = 8;
+const TAKEOFF_HEADINGS: usize = 16;
+const TAKEOFF_RUN_UP: f32 = 192.0;
+
+// an imperfect aim wanders off and back over about this long, rather than
+// jittering every frame
+const AIM_DRIFT_S: f32 = 0.2;
+// how long a jump is held, so a jump too early is over before landing
+const JUMP_TAP_S: f32 = 0.05;
+const LAND_TRACE: f32 = 1024.0;
+const TURN_RATE_MIN: f32 = 0.25;
+
+// hopping is given up on below what a circle jump's 1.1 times ground speed
+// is left with after landing, with this much slack for rounding
+const HOP_SPEED_FACTOR: f32 = 1.1;
+const HOP_SPEED_MARGIN: f32 = 1.0;
+
+// assisting allows the player some multiple of their usual error, averaged
+// over a few seconds
+const ASSIST_MARGIN: f32 = 2.0;
+const ASSIST_AVERAGE_S: f32 = 5.0;
+const ASSIST_THRESHOLD_MIN: Deg<f32> = Deg(1.0);
+const ASSIST_THRESHOLD_MAX: Deg<f32> = Deg(45.0);
+
+enum StrafeBotState {
+    Setup(f32),
+    // turned so far, and for how long
+    Takeoff(Deg<f32>, f32),
+    // jumped, is clockwise, and how sharply to curve, from 0 (straight) to 1
+    Flight(bool, bool, f32),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StartKind {
+    // hop from a standstill, strafing from the first jump
+    StandingJump,
+    // run up, then strafe along the ground while turning through the angle,
+    // jumping at the end
+    CircleJump,
+    // strafe along the ground from a standstill while turning through the
+    // angle, jumping at the end
+    Prestrafe,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StartRoutine {
+    pub kind: StartKind,
+    // the start begins facing this far clockwise of where it ends up
+    pub angle: Deg<f32>,
+    // across the runway, where the start begins
+    pub start_x: f32,
+}
+
+impl StartRoutine {
+    pub const STANDING_JUMP: Self = Self{
+        kind   : StartKind::StandingJump,
+        angle  : Deg(45.0),
+        start_x: 0.0,
+    };
+
+    pub const CIRCLE_JUMP_90: Self = Self{
+        kind   : StartKind::CircleJump,
+        angle  : Deg(90.0),
+        start_x: -320.0,
+    };
+
+    pub const CIRCLE_JUMP_150: Self = Self{
+        kind   : StartKind::CircleJump,
+        angle  : Deg(150.0),
+        start_x: -160.0,
+    };
+
+    pub const CIRCLE_JUMP_180: Self = Self{
+        kind   : StartKind::CircleJump,
+        angle  : Deg(180.0),
+        start_x: -160.0,
+    };
+
+    pub const CIRCLE_JUMP_270: Self = Self{
+        kind   : StartKind::CircleJump,
+        angle  : Deg(270.0),
+        start_x: 0.0,
+    };
+
+    pub const PRESTRAFE: Self = Self{
+        kind   : StartKind::Prestrafe,
+        angle  : Deg(90.0),
+        start_x: -320.0,
+    };
+}
+
+impl Default for StartRoutine {
+    fn default() -> Self {
+        Self::CIRCLE_JUMP_150
+    }
+}
+
+// how far from perfect the bot plays, with the spread of each kind of error
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Skill {
+    // wandering of the view about the best angle
+    pub aim_error: Deg<f32>,
+    // delay in changing keys, e.g. between strafes
+    pub key_latency_s: f32,
+    // how early or late each jump is
+    pub jump_timing_s: f32,
+    // the fastest turn varies by hop, as a fraction of MAX_TURN_RATE
+    pub turn_rate_spread: f32,
+    pub seed: u64,
+}
+
+impl Skill {
+    pub const PERFECT: Self = Self{
+        aim_error       : Deg(0.0),
+        key_latency_s   : 0.0,
+        jump_timing_s   : 0.0,
+        turn_rate_spread: 0.0,
+        seed            : 0,
+    };
+
+    // errors grow as the level falls from 1, playing perfectly, to 0
+    pub fn at_level(level: f32, seed: u64) -> Self {
+        let error = 1.0 - level.clamp(0.0, 1.0);
+        Self{
+            aim_error       : Deg(5.0) * error,
+            key_latency_s   : 0.15 * error,
+            jump_timing_s   : 0.1 * error,
+            turn_rate_spread: 1.0 * error,
+            seed,
+        }
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        Self{seed: 0, ..*self} == Self::PERFECT
+    }
+}
+
+impl Default for Skill {
+    fn default() -> Self {
+        Self::PERFECT
+    }
+}
+
+// normally distributed, by the Box-Muller transform
+fn normal(rng: &mut StdRng) -> f32 {
+    let u = 1.0 - rng.gen::<f32>();
+    let v = rng.gen::<f32>();
+    (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+}
+
+// what the errors in Skill have left the bot doing
+struct Flaws {
+    rng: StdRng,
+    aim_error: Rad<f32>,
+    // movement keys held, while new ones wait on key latency
+    keys: KeyState,
+    key_wait_s: f32,
+    key_latency_s: Option<f32>,
+    // negative jumps early, drawn each hop along with its fastest turn
+    jump_offset_s: f32,
+    jump_wait_s: f32,
+    turn_rate: f32,
+    was_grounded: bool,
+}
+
+impl Flaws {
+    fn new(seed: u64) -> Self {
+        Self{
+            rng: StdRng::seed_from_u64(seed),
+            aim_error: Rad::zero(),
+            keys: KeyState::default(),
+            key_wait_s: 0.0,
+            key_latency_s: None,
+            jump_offset_s: 0.0,
+            jump_wait_s: 0.0,
+            turn_rate: 1.0,
+            was_grounded: true,
+        }
+    }
+
+    // how long until the player falls onto whatever's below
+    fn time_to_land<G: Geometry + ?Sized>(geometry: &G, player: &PlayerState, gravity: f32) -> f32 {
+        // without gravity, nothing comes back down
+        if gravity <= 0.0 {
+            return f32::INFINITY;
+        }
+        let trace = geometry.trace(player.pos, player.pos - Vector3::unit_z() * LAND_TRACE, player.hull());
+        if !trace.is_hit() {
+            return f32::INFINITY;
+        }
+        let height = trace.fraction * LAND_TRACE;
+        let vel_z = player.vel.z;
+        (vel_z + (vel_z * vel_z + 2.0 * gravity * height).sqrt()) / gravity
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply<G: Geometry + ?Sized>(&mut self,
+        skill: &Skill,
+        dt: f32,
+        player: &PlayerState,
+        gravity: f32,
+        is_hopping: bool,
+        keys: KeyState,
+        turn_yaw: Rad<f32>,
+        geometry: &G,
+    )
+        -> (KeyState, Rad<f32>)
+    {
+        let is_grounded = player.is_grounded();
+        if self.was_grounded && !is_grounded {
+            self.jump_offset_s = normal(&mut self.rng) * skill.jump_timing_s;
+            self.turn_rate = (1.0 + normal(&mut self.rng) * skill.turn_rate_spread).max(TURN_RATE_MIN);
+        }
+        self.was_grounded = is_grounded;
+
+        let keep = (-dt / AIM_DRIFT_S).exp();
+        let aim_error = self.aim_error * keep +
+            Rad::from(skill.aim_error) * ((1.0 - keep * keep).sqrt() * normal(&mut self.rng));
+        // the turn's measured from wherever the view is, last frame's error
+        // included, so this frame's is simply added on
+        let turn_yaw = turn_yaw + aim_error;
+        self.aim_error = aim_error;
+
+        let moves = |keys: KeyState| (keys.key_w, keys.key_a, keys.key_s, keys.key_d);
+        if moves(keys) != moves(self.keys) {
+            let key_latency_s = match self.key_latency_s {
+                Some(key_latency_s) => key_latency_s,
+                None => {
+                    let key_latency_s = normal(&mut self.rng).abs() * skill.key_latency_s;
+                    self.key_latency_s = Some(key_latency_s);
+                    key_latency_s
+                },
+            };
+            self.key_wait_s += dt;
+            if self.key_wait_s >= key_latency_s {
+                self.keys = keys;
+                self.key_wait_s = 0.0;
+                self.key_latency_s = None;
+            }
+        } else {
+            self.key_wait_s = 0.0;
+            self.key_latency_s = None;
+        }
+
+        // keys.space is pressed on time; a late jump holds off from then,
+        // while an early one is tapped ahead of landing, and missed if the
+        // tap's over by then, only noticed as late as it was early
+        let space = if is_hopping {
+            self.jump_wait_s = if keys.space { self.jump_wait_s + dt } else { 0.0 };
+            let early_s = -self.jump_offset_s;
+            if early_s <= 0.0 || is_grounded {
+                keys.space && self.jump_wait_s >= self.jump_offset_s.abs()
+            } else {
+                let land_s = Self::time_to_land(geometry, player, gravity);
+                land_s <= early_s && land_s + JUMP_TAP_S > early_s
+            }
+        } else {
+            keys.space
+        };
+
+        (KeyState{
+            key_w: self.keys.key_w,
+            key_a: self.keys.key_a,
+            key_s: self.keys.key_s,
+            key_d: self.keys.key_d,
+            space,
+            ..keys
+        }, turn_yaw)
+    }
+}
+
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct StrafeConfig {
+    keys_cw: Option<KeyState>,
+    keys_ccw: Option<KeyState>,
+}
+
+impl StrafeConfig {
+    const KEYS_A: KeyState = KeyState {
+        key_a: true,
+        ..KEYS_DEFAULT
+    };
+
+    const KEYS_D: KeyState = KeyState {
+        key_d: true,
+        ..KEYS_DEFAULT
+    };
+
+    const KEYS_SA: KeyState = KeyState {
+        key_s: true,
+        key_a: true,
+        ..KEYS_DEFAULT
+    };
+
+    const KEYS_SD: KeyState = KeyState {
+        key_s: true,
+        key_d: true,
+        ..KEYS_DEFAULT
+    };
+
+    const KEYS_WA: KeyState = KeyState {
+        key_w: true,
+        key_a: true,
+        ..KEYS_DEFAULT
+    };
+
+    const KEYS_WD: KeyState = KeyState {
+        key_w: true,
+        key_d: true,
+        ..KEYS_DEFAULT
+    };
+
+    pub const PLAYER_KEYS: Self = Self{
+        keys_cw : None,
+        keys_ccw: None,
+    };
+
+    pub const STANDARD: Self = Self{
+        keys_cw : Some(Self::KEYS_WD),
+        keys_ccw: Some(Self::KEYS_WA),
+    };
+
+    pub const REVERSE: Self = Self{
+        keys_cw : Some(Self::KEYS_SA),
+        keys_ccw: Some(Self::KEYS_SD),
+    };
+
+    pub const HALF_BEAT_LEFT: Self = Self{
+        keys_cw : Some(Self::KEYS_D),
+        keys_ccw: Some(Self::KEYS_WA),
+    };
+
+    pub const HALF_BEAT_RIGHT: Self = Self{
+        keys_cw : Some(Self::KEYS_WD),
+        keys_ccw: Some(Self::KEYS_A),
+    };
+
+    pub const HIGH_SPEED: Self = Self{
+        keys_cw : Some(Self::KEYS_D),
+        keys_ccw: Some(Self::KEYS_A),
+    };
+
+    pub const SIDEWAYS_LEFT: Self = Self{
+        keys_cw : Some(Self::KEYS_WA),
+        keys_ccw: Some(Self::KEYS_SA),
+    };
+
+    pub const SIDEWAYS_RIGHT: Self = Self{
+        keys_cw : Some(Self::KEYS_SD),
+        keys_ccw: Some(Self::KEYS_WD),
+    };
+}
+
+// the ticks run over the next frame, assuming it's as long as this one; view
+// rotation is spread evenly over time, so each tick turns a little further
+struct Prediction<'a> {
+    kinematics: &'a Kinematics,
+    player: &'a PlayerState,
+    tick_s: f32,
+    ticks: usize,
+    // time until the next frame, including what's left over from this one
+    remainder_s: f32,
+    add_yaw: Rad<f32>,
+}
+
+impl<'a> Prediction<'a> {
+    fn new(kinematics: &'a Kinematics,
+        player: &'a PlayerState,
+        dt: f32,
+        tick_s: f32,
+        tick_remainder_s: f32,
+        add_yaw: Rad<f32>,
+    )
+        -> Self
+    {
+        let remainder_s = tick_remainder_s + dt;
+        let ticks = ((remainder_s / tick_s) as usize).max(1);
+        Self{kinematics, player, tick_s, ticks, remainder_s, add_yaw}
+    }
+
+    // from the view to the wish direction
+    fn key_angle(&self, keys: KeyState) -> Rad<f32> {
+        self.player.forward_dir().angle(self.player.wish_dir(keys, Rad::zero(), Rad::zero()))
+    }
+
+    fn vel_after(&self, keys: KeyState, turn: Rad<f32>) -> Vector3<f32> {
+        let player = self.player;
+        let key_angle = self.key_angle(keys);
+        let has_wish = player.wish_dir(keys, Rad::zero(), Rad::zero()).magnitude2() > 0.5;
+        // as the player would, so held or buffered presses count the same
+        let mut jump = player.clone();
+        let mut ground = player.ground_state(keys);
+        let mut vel = player.vel;
+        for i in 0..self.ticks {
+            let yaw = player.dir.0 + (self.add_yaw + turn) * ((i + 1) as f32 * self.tick_s / self.remainder_s).min(1.0);
+            let (sin, cos) = yaw.sin_cos();
+            let forward = Vector2::new(-sin, cos);
+            let (sin, cos) = (yaw + key_angle).sin_cos();
+            let wish_dir = if has_wish { Vector2::new(-sin, cos) } else { Vector2::zero() };
+            let space = jump.is_jumping(self.kinematics.jump_mode, keys, self.tick_s);
+            if space && ground.is_some() {
+                jump.use_jump();
+            }
+            self.kinematics.sim(&mut vel, self.tick_s, wish_dir, forward, ground, KeyState{space, ..keys});
+            ground = if space {
+                None
+            } else {
+                ground.map(|ground| Ground{
+                    landed_ticks: ground.landed_ticks + 1,
+                    ..ground
+                })
+            };
+        }
+        vel
+    }
+
+    // the view turn which puts the wish direction at angle from the velocity
+    fn turn_for(&self, keys: KeyState, angle: Rad<f32>) -> Rad<f32> {
+        let heading = Vector2::unit_y().angle(self.player.vel.xy());
+        let wish_yaw = self.player.dir.0 + self.add_yaw + self.key_angle(keys);
+        (heading + angle - wish_yaw).normalize_signed()
+    }
+
+    // the turn gaining the most speed with the wish direction to one side of
+    // the velocity; curving less holds nearer the velocity, trading
+    // acceleration for a straighter path
+    fn strafe_turn(&self, keys: KeyState, is_clockwise: bool, curve: f32) -> Rad<f32> {
+        let side = if is_clockwise { -1.0 } else { 1.0 };
+        let speed_at = |angle: Rad<f32>| self.vel_after(keys, self.turn_for(keys, angle * side)).xy().magnitude();
+        let (mut angle, best_speed) = theory::best_angle(speed_at);
+
+        // speed grows with the angle up to the best, so the least angle
+        // gaining some fraction of that can be bisected for
+        if curve < 1.0 {
+            let speed = self.player.vel.xy().magnitude();
+            let target_speed = speed + (best_speed - speed).max(0.0) * curve;
+            angle = theory::bisect(|angle| speed_at(angle) >= target_speed, Rad::zero(), angle);
+        }
+        self.turn_for(keys, angle * side)
+    }
+
+    // how fast the velocity turns, per second, strafing as well as possible
+    fn turn_rate(&self, keys: KeyState) -> Rad<f32> {
+        let vel = self.vel_after(keys, self.strafe_turn(keys, false, 1.0));
+        self.player.vel.xy().angle(vel.xy()) / (self.ticks as f32 * self.tick_s)
+    }
+}
+
+pub struct StrafeBot {
+    state: StrafeBotState,
+    pub config: StrafeConfig,
+    // steer around scenery, rather than hopping back and forth along a runway
+    pub avoid_obstacles: bool,
+    start: StartRoutine,
+    skill: Skill,
+    flaws: Flaws,
+    // where the bot would have the view by now, turning no faster than it
+    // does itself, for comparing with a player turning on their own
+    view_yaw: Rad<f32>,
+}
+
+// speed left after jumping on landing, once landing friction and any bunnyhop
+// cap have had their share
+fn landing_speed(kinematics: &Kinematics, tick_s: f32, speed: f32) -> f32 {
+    let mut vel = Vector3::unit_y() * speed;
+    let ground = Ground{
+        norm: Vector3::unit_z(),
+        is_edge: false,
+        stance: Stance::Standing,
+        is_double_jump: false,
+        landed_ticks: 0,
+        knockback: false,
+    };
+    let keys = KeyState{space: true, ..KEYS_DEFAULT};
+    kinematics.sim(&mut vel, tick_s, Vector2::zero(), Vector2::unit_y(), Some(ground), keys);
+    vel.xy().magnitude()
+}
+
+fn clamp_angle<T: Angle>(x: T, max: T) -> T {
+    let x = x.normalize_signed();
+    if x < -max {
+        -max
+    } else if x > max {
+        max
+    } else {
+        x
+    }
+}
+
+impl StrafeBot {
+    pub fn new(config: StrafeConfig) -> Self {
+        Self{
+            state: StrafeBotState::Setup(0.0),
+            config,
+            avoid_obstacles: false,
+            start: StartRoutine::default(),
+            skill: Skill::default(),
+            flaws: Flaws::new(0),
+            view_yaw: Rad::zero(),
+        }
+    }
+
+    pub fn view_yaw(&self) -> Rad<f32> {
+        self.view_yaw
+    }
+
+    pub fn start(&self) -> StartRoutine {
+        self.start
+    }
+
+    // starting over, so a new routine is shown from the beginning
+    pub fn set_start(&mut self, start: StartRoutine) {
+        if start != self.start {
+            self.start = start;
+            self.state = StrafeBotState::Setup(0.0);
+        }
+    }
+
+    pub fn skill(&self) -> Skill {
+        self.skill
+    }
+
+    // starting over from the seed, so each run with a skill plays the same;
+    // a perfect bot has no use for one
+    pub fn set_skill(&mut self, skill: Skill) {
+        if skill != self.skill && !(skill.is_perfect() && self.skill.is_perfect()) {
+            self.skill = skill;
+            self.flaws = Flaws::new(skill.seed);
+            self.state = StrafeBotState::Setup(0.0);
+        }
+    }
+
+    pub fn is_setting_up(&self) -> bool {
+        matches!(self.state, StrafeBotState::Setup(..))
+    }
+
+    // distance along an arc, turning by turn over its length, before the
+    // player would run into something
+    fn clearance<G: Geometry + ?Sized>(geometry: &G,
+        player: &PlayerState,
+        heading: Rad<f32>,
+        turn: Rad<f32>,
+        distance: f32,
+    )
+        -> f32
+    {
+        let hull = player.hull();
+        let step = distance / LOOK_AHEAD_STEPS as f32;
+        let mut pos = player.pos + Vector3::unit_z() * STEP_SIZE;
+        let mut heading = heading;
+        for i in 0..LOOK_AHEAD_STEPS {
+            heading += turn / LOOK_AHEAD_STEPS as f32;
+            let (sin, cos) = heading.sin_cos();
+            let end = pos + Vector3::new(-sin, cos, 0.0) * step;
+            let trace = geometry.trace(pos, end, hull);
+            if trace.is_hit() {
+                return (i as f32 + trace.fraction) * step;
+            }
+            pos = end;
+        }
+        distance
+    }
+
+    // the heading with the most room, both for the start's run up and
+    // straight on from it, nearest to where a start from the current yaw
+    // would end
+    fn takeoff_heading<G: Geometry + ?Sized>(geometry: &G, player: &PlayerState, angle: Deg<f32>) -> Rad<f32> {
+        let yaw = player.dir.0 + Rad::from(angle);
+        let room = |heading: Rad<f32>| {
+            let run_up = Self::clearance(geometry, player, heading - Rad::from(angle), angle.into(), TAKEOFF_RUN_UP);
+            let straight = Self::clearance(geometry, player, heading, Rad::zero(), LOOK_AHEAD_MIN);
+            (run_up / TAKEOFF_RUN_UP).min(straight / LOOK_AHEAD_MIN)
+        };
+        (0..TAKEOFF_HEADINGS)
+            .map(|i| Rad::full_turn() * (i as f32 / TAKEOFF_HEADINGS as f32))
+            .map(|heading| (heading, room(heading)))
+            .fold(None, |best: Option<(Rad<f32>, f32)>, (heading, clearance)| {
+                match best {
+                    Some((best_heading, best_clearance)) if best_clearance > clearance ||
+                        (best_clearance == clearance &&
+                            (best_heading - yaw).normalize_signed().0.abs() <= (heading - yaw).normalize_signed().0.abs()) =>
+                        Some((best_heading, best_clearance)),
+                    _ => Some((heading, clearance)),
+                }
+            })
+            .map_or(yaw, |(heading, _)| heading)
+    }
+
+    // room along the path a strafe would curve over the next look_ahead_s,
+    // and how long that path is
+    fn strafe_clearance<G: Geometry + ?Sized>(geometry: &G,
+        player: &PlayerState,
+        turn_rate: Rad<f32>,
+        is_clockwise: bool,
+        curve: f32,
+        look_ahead_s: f32,
+    )
+        -> (f32, f32)
+    {
+        let speed = player.vel.xy().magnitude();
+        let heading = Vector2::unit_y().angle(player.vel.xy());
+        let distance = (speed * look_ahead_s).max(LOOK_AHEAD_MIN);
+        let turn = turn_rate * (distance / speed.max(1.0)) * curve;
+        let turn = if is_clockwise { -turn } else { turn };
+        (Self::clearance(geometry, player, heading, turn, distance), distance)
+    }
+
+    // picks the side and sharpness of a curve; swapping sides each hop keeps
+    // a straight course, so that's preferred while it's clear
+    fn steer<G: Geometry + ?Sized>(geometry: &G,
+        player: &PlayerState,
+        turn_rate: Rad<f32>,
+        was_clockwise: bool,
+        look_ahead_s: f32,
+    )
+        -> (bool, f32)
+    {
+        let mut best = (!was_clockwise, 1.0, -1.0);
+        for &(is_clockwise, curve) in &[
+            (!was_clockwise, 1.0),
+            ( was_clockwise, 1.0),
+            (!was_clockwise, 0.5),
+            ( was_clockwise, 0.5),
+            (!was_clockwise, 0.0),
+        ] {
+            let (clearance, distance) = Self::strafe_clearance(geometry, player, turn_rate, is_clockwise, curve, look_ahead_s);
+            if clearance >= distance {
+                return (is_clockwise, curve);
+            }
+            if clearance > best.2 {
+                best = (is_clockwise, curve, clearance);
+            }
+        }
+        (best.0, best.1)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn sim<G: Geometry + ?Sized>(&mut self, dt: f32,
+        player: &PlayerState,
+        keys: KeyState,
+        kinematics: &Kinematics,
+        tick_s: f32,
+        tick_remainder_s: f32,
+        add_yaw: Rad<f32>,
+        add_pitch: Rad<f32>,
+        geometry: &G,
+    )
+        -> (KeyState, Rad<f32>, Rad<f32>)
+    {
+        let speed = player.vel.xy().magnitude();
+        let speed_limit = kinematics.move_ground.max_speed;
+        let hop_speed = landing_speed(kinematics, tick_s, HOP_SPEED_FACTOR * speed_limit) - HOP_SPEED_MARGIN;
+        let prediction = Prediction::new(kinematics, player, dt, tick_s, tick_remainder_s, add_yaw);
+        let yaw   = player.dir.0 + add_yaw;
+        let pitch = player.dir.1 + add_pitch;
+        let max_turn: Rad<f32> = (MAX_TURN_RATE * self.flaws.turn_rate * dt).into();
+        let mut max_turn_yaw = max_turn;
+        // jump as soon as the player lands, even mid-frame, but release while
+        // rising, as some settings need jump released in between
+        let is_grounded = player.is_grounded();
+        let fall = Vector3::unit_z() * (player.vel.z * prediction.remainder_s).min(0.0);
+        let is_landing = is_grounded || geometry.trace(player.pos, player.pos + fall, player.hull()).is_hit();
+        let start = self.start;
+        let (out_keys, turn_yaw) = loop { match &mut self.state {
+            StrafeBotState::Setup(duration) if self.avoid_obstacles => {
+                // take off wherever the player stops, so the start ends
+                // facing open space
+                let target_angle = Self::takeoff_heading(geometry, player, start.angle) - Rad::from(start.angle);
+                if speed < 10.0 {
+                    *duration += dt;
+                    if *duration > START_DELAY_S {
+                        self.state = StrafeBotState::Takeoff(Deg::zero(), 0.0);
+                        continue;
+                    }
+                } else {
+                    *duration = 0.0;
+                }
+                break (KeyState::default(), target_angle - yaw);
+            },
+            StrafeBotState::Setup(duration) => {
+                let target_angle = -start.angle;
+                let move_x = start.start_x - player.pos.x;
+                if move_x.abs() < 10.0 && speed < 10.0 {
+                    *duration += dt;
+                    if *duration > START_DELAY_S {
+                        self.state = StrafeBotState::Takeoff(Deg::zero(), 0.0);
+                        continue;
+                    } else {
+                        break (KeyState::default(), Into::<Rad<_>>::into(target_angle) - yaw);
+                    }
+                } else {
+                    *duration = 0.0;
+                }
+                let move_angle: Rad<f32> = if move_x > 0.0 {
+                    Rad::zero()
+                } else {
+                    Rad::turn_div_2()
+                };
+                let (ny, nx) = (move_angle - yaw).sin_cos();
+                const MOVE_THRESHOLD: f32 = 0.383;
+                let out_keys = KeyState{
+                    key_w: ny >  MOVE_THRESHOLD,
+                    key_a: nx < -MOVE_THRESHOLD,
+                    key_s: ny < -MOVE_THRESHOLD,
+                    key_d: nx >  MOVE_THRESHOLD,
+                    ..Default::default()
+                };
+                break (out_keys, Into::<Rad<_>>::into(target_angle) - yaw);
+            },
+            StrafeBotState::Takeoff(turned, duration) => {
+                *duration += dt;
+                // a standing jump keeps strafing the one way until it's fast
+                // enough to hop on as usual, even after landing
+                let is_done = match start.kind {
+                    StartKind::StandingJump => landing_speed(kinematics, tick_s, speed) > hop_speed,
+                    StartKind::CircleJump |
+                    StartKind::Prestrafe    => *turned >= start.angle,
+                };
+                if is_done {
+                    self.state = StrafeBotState::Flight(false, false, 1.0);
+                    continue;
+                }
+                // ran into something, so look for more room
+                if self.avoid_obstacles && *duration > START_DELAY_S && speed < 0.5 * speed_limit {
+                    self.state = StrafeBotState::Setup(0.0);
+                    continue;
+                }
+                let is_strafing = match start.kind {
+                    StartKind::CircleJump => speed > 0.99 * speed_limit,
+                    StartKind::StandingJump |
+                    StartKind::Prestrafe  => speed > 0.0001,
+                };
+                let out_keys = KeyState{
+                    key_w: true,
+                    key_a: is_strafing || start.kind != StartKind::CircleJump,
+                    space: is_landing && start.kind == StartKind::StandingJump,
+                    ..Default::default()
+                };
+                let turn_angle = if is_strafing {
+                    prediction.strafe_turn(out_keys, false, 1.0)
+                } else {
+                    Rad::zero()
+                };
+                *turned += clamp_angle(turn_angle, max_turn).into();
+                break (out_keys, turn_angle);
+            }
+            StrafeBotState::Flight(jumped, is_clockwise, curve) => {
+                if speed < hop_speed {
+                    self.state = StrafeBotState::Setup(0.0);
+                    continue;
+                }
+                let config = &self.config;
+                let strafe_keys = |is_clockwise: bool| KeyState{
+                    space: is_landing,
+                    ..Default::default()
+                } | (if is_clockwise {
+                    config.keys_cw
+                } else {
+                    config.keys_ccw
+                }).unwrap_or(keys);
+                // on top of keeping up with the velocity, however quickly it turns
+                let turn_rate = prediction.turn_rate(strafe_keys(false));
+                max_turn_yaw = max_turn + Rad(turn_rate.0.abs()) * dt;
+                if is_grounded {
+                    if !*jumped && self.avoid_obstacles {
+                        *jumped = true;
+                        let (steer_clockwise, steer_curve) = Self::steer(geometry, player, turn_rate, *is_clockwise, LOOK_AHEAD_S);
+                        *is_clockwise = steer_clockwise;
+                        *curve = steer_curve;
+                    } else if !*jumped {
+                        *jumped = true;
+                        if player.pos.x < -512.0 {
+                            *is_clockwise = true;
+                        } else if player.pos.x > 512.0 {
+                            *is_clockwise = false;
+                        } else if player.vel.x < -80.0 {
+                            *is_clockwise = true;
+                        } else if player.vel.x > 80.0 {
+                            *is_clockwise = false;
+                        }
+                    }
+                } else {
+                    *jumped = false;
+                    // something's come into view mid-air, e.g. from around a corner
+                    if self.avoid_obstacles {
+                        let (clearance, distance) = Self::strafe_clearance(geometry, player, turn_rate, *is_clockwise, *curve, LOOK_AHEAD_AIR_S);
+                        if clearance < distance {
+                            let (steer_clockwise, steer_curve) = Self::steer(geometry, player, turn_rate, !*is_clockwise, LOOK_AHEAD_AIR_S);
+                            *is_clockwise = steer_clockwise;
+                            *curve = steer_curve;
+                        }
+                    }
+                }
+                let out_keys = strafe_keys(*is_clockwise);
+                let has_wish = player.wish_dir(out_keys, Rad::zero(), Rad::zero()).magnitude2() > 0.5;
+                let turn_angle = if has_wish && speed > 0.0001 {
+                    prediction.strafe_turn(out_keys, *is_clockwise, *curve)
+                } else {
+                    Rad::zero()
+                };
+                break (out_keys, turn_angle);
+            }
+        }};
+        let view_yaw = if self.is_setting_up() { yaw } else { self.view_yaw };
+        self.view_yaw = view_yaw + clamp_angle(yaw + turn_yaw - view_yaw, max_turn_yaw);
+        let is_hopping = match self.state {
+            StrafeBotState::Setup(..) => false,
+            StrafeBotState::Takeoff(..) => start.kind == StartKind::StandingJump,
+            StrafeBotState::Flight(..) => true,
+        };
+        // setting up isn't part of the demonstration, so it's done perfectly
+        let (out_keys, turn_yaw) = if self.skill.is_perfect() || self.is_setting_up() {
+            (out_keys, turn_yaw)
+        } else {
+            self.flaws.apply(&self.skill, dt, player, kinematics.gravity, is_hopping, out_keys, turn_yaw, geometry)
+        };
+        let turn_pitch = Into::<Rad<_>>::into(Deg(90.0)) - pitch;
+        (out_keys,
+            clamp_angle(turn_yaw  , max_turn_yaw),
+            clamp_angle(turn_pitch, max_turn))
+    }
+}
+
+// lets the player strafe, stepping in only when they stray further from the
+// bot than they usually do, so the help fades as they get more accurate
+pub struct Assist {
+    mean_error: Rad<f32>,
+}
+
+impl Assist {
+    pub fn new() -> Self {
+        Self{
+            mean_error: Rad::from(ASSIST_THRESHOLD_MAX) / ASSIST_MARGIN,
+        }
+    }
+
+    pub fn threshold(&self) -> Rad<f32> {
+        let threshold = self.mean_error * ASSIST_MARGIN;
+        if threshold < ASSIST_THRESHOLD_MIN.into() {
+            ASSIST_THRESHOLD_MIN.into()
+        } else if threshold > ASSIST_THRESHOLD_MAX.into() {
+            ASSIST_THRESHOLD_MAX.into()
+        } else {
+            threshold
+        }
+    }
+
+    // error is measured before the correction for it is applied
+    pub fn update(&mut self, dt: f32, error: Rad<f32>) {
+        let error = Rad(error.normalize_signed().0.abs());
+        self.mean_error += (error - self.mean_error) * (1.0 - (-dt / ASSIST_AVERAGE_S).exp());
+    }
+
+    // the part of an error past the threshold
+    pub fn correction(&self, error: Rad<f32>) -> Rad<f32> {
+        let error = error.normalize_signed();
+        error - clamp_angle(error, self.threshold())
+    }
+
+    // whether a player's wish direction is too far from the bot's to keep;
+    // not wishing to move at all is always too far, unless the bot agrees
+    pub fn is_off(&self, wish_dir: Vector2<f32>, bot_wish_dir: Vector2<f32>) -> bool {
+        if bot_wish_dir.magnitude2() < 0.5 {
+            false
+        } else if wish_dir.magnitude2() < 0.5 {
+            true
+        } else {
+            Rad(wish_dir.angle(bot_wish_dir).0.abs()) > self.threshold()
+        }
+    }
+}
+
+impl Default for Assist {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::{Matrix4, Point3, SquareMatrix};
+
+    use crate::collision::Brush;
+    use crate::player::{
+        JumpMode,
+        MOVE_CPMA_LIKE,
+        MOVE_HYBRID,
+        MOVE_QW_LIKE,
+        MOVE_SOURCE_LIKE,
+        MOVE_VQ3_LIKE,
+        MOVE_WARSOW_LIKE,
+    };
+    use crate::theory::Strafe;
+
+    const TICK_S: f32 = 0.008;
+
+    fn wall(x0: f32, y0: f32, x1: f32, y1: f32) -> Brush {
+        Brush::from_box(Point3::new(x0, y0, 0.0), Point3::new(x1, y1, 256.0), Matrix4::identity())
+    }
+
+    fn grounded() -> PlayerState {
+        PlayerState{ground: Some(Vector3::unit_z()), landed_ticks: Some(0), ..PlayerState::default()}
+    }
+
+    fn predicts_jump(jump_mode: JumpMode, player: &PlayerState, space: bool) -> bool {
+        let kinematics = Kinematics{jump_mode, ..MOVE_VQ3_LIKE};
+        let prediction = Prediction::new(&kinematics, player, 4.0 * TICK_S, TICK_S, 0.0, Rad(0.0));
+        let keys = KeyState{space, ..KeyState::default()};
+        prediction.vel_after(keys, Rad(0.0)).z > 0.0
+    }
+
+    #[test]
+    fn prediction_jump_modes() {
+        let held = PlayerState{jump_held: true, ..grounded()};
+        assert!(predicts_jump(JumpMode::Hold, &held, true));
+        assert!(!predicts_jump(JumpMode::RequireRelease, &held, true));
+        assert!(predicts_jump(JumpMode::RequireRelease, &grounded(), true));
+        // a press buffered before landing jumps with jump since released
+        let buffered = PlayerState{jump_buffer_s: Some(0.05), ..grounded()};
+        assert!(predicts_jump(JumpMode::Buffer(0.1), &buffered, false));
+        assert!(!predicts_jump(JumpMode::Buffer(0.1), &grounded(), false));
+    }
+
+    #[test]
+    fn clearance() {
+        let walls = [wall(-512.0, 256.0, 512.0, 320.0)];
+        let player = PlayerState::default();
+        let room = |heading: f32, turn: f32| StrafeBot::clearance(&walls[..], &player, Rad(heading), Rad(turn), 512.0);
+        // the hull stops its radius short of the wall
+        assert!((room(0.0, 0.0) - 240.0).abs() < 0.1, "{}", room(0.0, 0.0));
+        assert_eq!(room(std::f32::consts::FRAC_PI_2, 0.0), 512.0);
+        // curving half a turn over the distance comes back short of it
+        assert_eq!(room(0.0, std::f32::consts::PI), 512.0);
+    }
+
+    #[test]
+    fn takeoff_heading() {
+        let player = PlayerState::default();
+        let angle = Deg(90.0);
+        // in the open, a start from the current view ends a quarter turn clockwise
+        let open: [Brush; 0] = [];
+        let heading = StrafeBot::takeoff_heading(&open[..], &player, angle);
+        assert!((heading - Rad::from(angle)).normalize_signed().0.abs() < 0.001);
+
+        // boxed into a corner, both the run up and what follows are clear
+        let walls = [wall(-1e4, 64.0, 1e4, 128.0), wall(-128.0, -1e4, -64.0, 1e4)];
+        let heading = StrafeBot::takeoff_heading(&walls[..], &player, angle);
+        let run_up = StrafeBot::clearance(&walls[..], &player, heading - Rad::from(angle), angle.into(), TAKEOFF_RUN_UP);
+        let straight = StrafeBot::clearance(&walls[..], &player, heading, Rad::zero(), LOOK_AHEAD_MIN);
+        assert_eq!(run_up, TAKEOFF_RUN_UP);
+        assert_eq!(straight, LOOK_AHEAD_MIN);
+    }
+
+    #[test]
+    fn steer() {
+        let player = PlayerState{vel: Vector3::new(0.0, 600.0, 0.0), ..PlayerState::default()};
+        let steer = |walls: &[Brush]| StrafeBot::steer(walls, &player, Rad(1.0), true, LOOK_AHEAD_S);
+        // sides swap each hop while there's room
+        assert_eq!(steer(&[]), (false, 1.0));
+        // but not into a wall
+        assert_eq!(steer(&[wall(-1e4, -1e4, -96.0, 1e4)]), (true, 1.0));
+        // and between two walls, straight on
+        assert_eq!(steer(&[wall(-1e4, -1e4, -96.0, 1e4), wall(96.0, -1e4, 1e4, 1e4)]), (false, 0.0));
+    }
+
+    // runs the bot a tick a frame on open ground, with the player before and
+    // after each tick
+    fn fly<F: FnMut(&StrafeBot, KeyState, &PlayerState, &PlayerState)>(bot: &mut StrafeBot,
+        kinematics: &Kinematics,
+        duration_s: f32,
+        mut on_tick: F)
+    {
+        let floor = [Brush::from_box(Point3::new(-1e5, -1e5, -16.0), Point3::new(1e5, 1e5, 0.0), Matrix4::identity())];
+        let mut player = PlayerState::default();
+        for _ in 0..(duration_s / TICK_S) as usize {
+            let (keys, turn_yaw, turn_pitch) = bot.sim(TICK_S, &player, KeyState::default(), kinematics, TICK_S, 0.0, Rad::zero(), Rad::zero(), &floor[..]);
+            player.add_rotation(turn_yaw, turn_pitch);
+            let before = player.clone();
+            player.sim_kinematics(kinematics, TICK_S, keys, &floor[..]);
+            on_tick(bot, keys, &before, &player);
+        }
+    }
+
+    #[test]
+    fn hops_gain_as_theory() {
+        for kinematics in &[MOVE_VQ3_LIKE, MOVE_QW_LIKE, MOVE_HYBRID, MOVE_CPMA_LIKE, MOVE_SOURCE_LIKE, MOVE_WARSOW_LIKE] {
+            let mut bot = StrafeBot::new(StrafeConfig::STANDARD);
+            // speed gained over each hop, by the bot and in theory, and
+            // whether it strafed the same way as the last
+            let mut hops = Vec::new();
+            let mut hop = (0.0, 0.0, None);
+            let mut last_side = None;
+            fly(&mut bot, kinematics, 10.0, |bot, keys, before, after| {
+                let side = match bot.state {
+                    StrafeBotState::Flight(_, is_clockwise, _) => Some(is_clockwise),
+                    _ => None,
+                };
+                if side.is_none() {
+                    hop = (0.0, 0.0, None);
+                } else if !before.is_grounded() && !after.is_grounded() {
+                    hop.0 += after.vel.xy().magnitude() - before.vel.xy().magnitude();
+                    hop.1 += Strafe::simulate(kinematics, before.vel, None, keys, TICK_S).gain;
+                    hop.2 = Some(side == last_side);
+                } else if after.is_grounded() && hop.2.is_some() {
+                    hops.push(hop);
+                    hop = (0.0, 0.0, None);
+                    last_side = side;
+                }
+            });
+            assert!(hops.len() >= 6, "only {} hops", hops.len());
+            for &(gain, theory_gain, is_same_side) in &hops {
+                // switching sides costs some while the view turns across
+                let min_gain = if is_same_side == Some(true) { 0.995 } else { 0.5 } * theory_gain;
+                assert!(gain >= min_gain && gain <= 1.001 * theory_gain,
+                    "gained {} of {} in theory", gain, theory_gain);
+            }
+        }
+    }
+
+    #[test]
+    fn start_routines() {
+        for &start in &[
+            StartRoutine::STANDING_JUMP,
+            StartRoutine::CIRCLE_JUMP_90,
+            StartRoutine::CIRCLE_JUMP_150,
+            StartRoutine::CIRCLE_JUMP_180,
+            StartRoutine::CIRCLE_JUMP_270,
+            StartRoutine::PRESTRAFE,
+        ] {
+            let mut bot = StrafeBot::new(StrafeConfig::STANDARD);
+            bot.set_start(start);
+            let mut takeoff = None;
+            let mut resets = 0;
+            fly(&mut bot, &MOVE_VQ3_LIKE, 6.0, |bot, _, _, after| {
+                let is_flying = matches!(bot.state, StrafeBotState::Flight(..));
+                if is_flying && takeoff.is_none() {
+                    takeoff = Some(after.vel);
+                }
+                if bot.is_setting_up() && takeoff.is_some() {
+                    resets += 1;
+                }
+            });
+            // each start ends faster than running, heading down the runway
+            let vel = takeoff.unwrap_or_else(|| panic!("{:?} never took off", start));
+            assert!(vel.xy().magnitude() > MOVE_VQ3_LIKE.move_ground.max_speed, "{:?} took off at {}", start, vel.xy().magnitude());
+            let heading = Deg::from(Vector2::unit_y().angle(vel.xy()));
+            assert!(heading.0.abs() < 30.0, "{:?} took off heading {:?}", start, heading);
+            assert_eq!(resets, 0, "{:?} fell back to setting up", start);
+        }
+    }
+    #[test]
+    fn skill_seeds() {
+        let trace = |seed: u64| {
+            let mut bot = StrafeBot::new(StrafeConfig::STANDARD);
+            bot.set_skill(Skill::at_level(0.5, seed));
+            let mut trace = Vec::new();
+            fly(&mut bot, &MOVE_VQ3_LIKE, 4.0, |_, keys, _, after| trace.push((keys, after.pos)));
+            trace
+        };
+        assert!(trace(1) == trace(1), "the same seed flew differently");
+        assert!(trace(1) != trace(2), "different seeds flew the same");
+    }
+
+    #[test]
+    fn time_to_land_without_gravity() {
+        let floor = [Brush::from_box(Point3::new(-1e5, -1e5, -16.0), Point3::new(1e5, 1e5, 0.0), Matrix4::identity())];
+        let player = PlayerState{pos: Point3::new(0.0, 0.0, 64.0), ..PlayerState::default()};
+        assert!(Flaws::time_to_land(&floor[..], &player, 800.0).is_finite());
+        assert_eq!(Flaws::time_to_land(&floor[..], &player, 0.0), f32::INFINITY);
+        assert_eq!(Flaws::time_to_land(&floor[..], &player, -800.0), f32::INFINITY);
+    }
+    fn assert_deg(actual: Rad<f32>, expected: f32) {
+        assert!((Deg::from(actual).0 - expected).abs() < 0.001, "{:?} != {}", Deg::from(actual), expected);
+    }
+
+    #[test]
+    fn assist_update() {
+        let mut assist = Assist::new();
+        assert_deg(assist.threshold(), 45.0);
+        // a time constant moves the mean 1 - 1/e of the way to the error
+        assist.update(ASSIST_AVERAGE_S, Deg(-10.0).into());
+        assert_deg(assist.mean_error, 10.0 + 12.5 / std::f32::consts::E);
+        for _ in 0..100 {
+            assist.update(1.0, Rad::zero());
+        }
+        assert_deg(assist.threshold(), 1.0);
+        for _ in 0..100 {
+            assist.update(1.0, Deg(370.0).into());
+        }
+        assert_deg(assist.mean_error, 10.0);
+        assert_deg(assist.threshold(), 20.0);
+        for _ in 0..100 {
+            assist.update(1.0, Deg(90.0).into());
+        }
+        assert_deg(assist.threshold(), 45.0);
+    }
+
+    #[test]
+    fn assist_correction() {
+        let assist = Assist::new();
+        assert_deg(assist.correction(Deg(30.0).into()), 0.0);
+        assert_deg(assist.correction(Deg(-45.0).into()), 0.0);
+        assert_deg(assist.correction(Deg(60.0).into()), 15.0);
+        assert_deg(assist.correction(Deg(-60.0).into()), -15.0);
+        assert_deg(assist.correction(Deg(300.0).into()), -15.0);
+    }
+
+    #[test]
+    fn assist_is_off() {
+        let assist = Assist::new();
+        let at = |angle: f32| {
+            let (sin, cos) = Rad::from(Deg(angle)).sin_cos();
+            Vector2::new(-sin, cos)
+        };
+        assert!(!assist.is_off(Vector2::zero(), Vector2::zero()));
+        assert!(!assist.is_off(at(90.0), Vector2::zero()));
+        assert!(assist.is_off(Vector2::zero(), at(0.0)));
+        assert!(!assist.is_off(at(40.0), at(0.0)));
+        assert!(!assist.is_off(at(-40.0), at(0.0)));
+        assert!(assist.is_off(at(50.0), at(0.0)));
+        assert!(!assist.is_off(at(170.0), at(-170.0)));
+        assert!(assist.is_off(at(-50.0), at(0.0)));
+    }
+}
\ No newline at end of file
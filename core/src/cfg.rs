@@ -0,0 +1,395 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::player::{JumpMode, Kinematics, Movement};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Dialect {
+    Quake3,
+    QuakeWorld,
+    Source,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Cvar {
+    Gravity,
+    Speed,
+    Accelerate,
+    AirAccelerate,
+    AirMaxSpeed,
+    WaterAccelerate,
+    Friction,
+    WaterFriction,
+    StopSpeed,
+    MaxVelocity,
+    EdgeFriction,
+    JumpImpulse,
+    AutoHop,
+    // recognised, but has no equivalent here
+    Unsupported,
+}
+
+impl Cvar {
+    // other speeds and accelerations are scaled by these, so they must be positive
+    fn is_speed(self) -> bool {
+        matches!(self, Cvar::Speed | Cvar::AirMaxSpeed | Cvar::MaxVelocity)
+    }
+}
+
+// commands which often share a config with cvars, but don't affect movement
+const COMMANDS: &[&str] = &[
+    "alias",
+    "bind",
+    "echo",
+    "exec",
+    "unbind",
+    "unbindall",
+    "wait",
+];
+
+impl Dialect {
+    fn cvar(self, name: &str) -> Option<Cvar> {
+        match self {
+            // vanilla Quake 3 hardcodes the pm_ values; mods such as DeFRaG and
+            // CPMA expose them as cvars
+            Dialect::Quake3 => match name {
+                "g_gravity"          => Some(Cvar::Gravity),
+                "g_speed"            => Some(Cvar::Speed),
+                "pm_accelerate"      => Some(Cvar::Accelerate),
+                "pm_airaccelerate"   => Some(Cvar::AirAccelerate),
+                "pm_wateraccelerate" => Some(Cvar::WaterAccelerate),
+                "pm_friction"        => Some(Cvar::Friction),
+                "pm_waterfriction"   => Some(Cvar::WaterFriction),
+                "pm_stopspeed"       => Some(Cvar::StopSpeed),
+                "pm_jumpvelocity"    => Some(Cvar::JumpImpulse),
+                "g_knockback" | "pmove_fixed" | "pmove_msec" | "sv_fps" => Some(Cvar::Unsupported),
+                _ => None,
+            },
+            Dialect::QuakeWorld => match name {
+                "sv_gravity"         => Some(Cvar::Gravity),
+                "sv_maxspeed"        => Some(Cvar::Speed),
+                "sv_accelerate"      => Some(Cvar::Accelerate),
+                "sv_wateraccelerate" => Some(Cvar::WaterAccelerate),
+                "sv_friction"        => Some(Cvar::Friction),
+                "sv_waterfriction"   => Some(Cvar::WaterFriction),
+                "sv_stopspeed"       => Some(Cvar::StopSpeed),
+                "sv_maxvelocity"     => Some(Cvar::MaxVelocity),
+                "edgefriction"       => Some(Cvar::EdgeFriction),
+                // QuakeWorld's air movement uses sv_accelerate instead
+                "sv_airaccelerate" | "sv_spectatormaxspeed" | "sv_maxtic" | "pm_bunnyspeedcap" => Some(Cvar::Unsupported),
+                _ => None,
+            },
+            Dialect::Source => match name {
+                "sv_gravity"            => Some(Cvar::Gravity),
+                "sv_maxspeed"           => Some(Cvar::Speed),
+                "sv_accelerate"         => Some(Cvar::Accelerate),
+                "sv_airaccelerate"      => Some(Cvar::AirAccelerate),
+                "sv_air_max_wishspeed"  => Some(Cvar::AirMaxSpeed),
+                "sv_wateraccelerate"    => Some(Cvar::WaterAccelerate),
+                "sv_friction"           => Some(Cvar::Friction),
+                "sv_waterfriction"      => Some(Cvar::WaterFriction),
+                "sv_stopspeed"          => Some(Cvar::StopSpeed),
+                "sv_maxvelocity"        => Some(Cvar::MaxVelocity),
+                "sv_jump_impulse"       => Some(Cvar::JumpImpulse),
+                "sv_autobunnyhopping"   => Some(Cvar::AutoHop),
+                "sv_enablebunnyhopping" | "sv_staminajumpcost" | "sv_staminalandcost"
+                    | "sv_bounce" | "sv_backspeed" => Some(Cvar::Unsupported),
+                _ => None,
+            },
+        }
+    }
+}
+
+// splits a line into commands and their arguments, honoring quotes, `;`
+// separators and `//` comments
+fn tokenize(line: &str) -> Vec<Vec<String>> {
+    let mut commands = vec![];
+    let mut args = vec![];
+    let mut token: Option<String> = None;
+    let mut is_quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_quoted {
+            if c == '"' {
+                is_quoted = false;
+                args.extend(token.take());
+            } else {
+                token.get_or_insert_with(String::new).push(c);
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                args.extend(token.take());
+                token = Some(String::new());
+                is_quoted = true;
+            }
+            '/' if chars.peek() == Some(&'/') => break,
+            ';' => {
+                args.extend(token.take());
+                commands.push(std::mem::take(&mut args));
+            }
+            c if c.is_whitespace() => args.extend(token.take()),
+            c => token.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(token.take());
+    commands.push(args);
+    commands.retain(|args| !args.is_empty());
+    commands
+}
+
+fn with_speed(movement: Movement, max_speed: f32) -> Movement {
+    Movement{max_speed, accel: movement.accel / movement.max_speed * max_speed}
+}
+
+// returns warnings for anything which couldn't be applied
+pub fn apply_cfg(dialect: Dialect, cfg: &str, kinematics: &mut Kinematics) -> Vec<String> {
+    let mut warnings = vec![];
+    let mut values = vec![];
+    for (i, line) in cfg.lines().enumerate() {
+        for args in tokenize(line) {
+            // Quake 3 sets cvars through set, seta, sets or setu
+            let args = match args[0].to_lowercase().as_str() {
+                "set" | "seta" | "sets" | "setu" if dialect == Dialect::Quake3 => &args[1..],
+                _ => &args[..],
+            };
+            let name = match args.first() {
+                Some(name) => name.to_lowercase(),
+                None => continue,
+            };
+            if COMMANDS.contains(&name.as_str()) {
+                continue;
+            }
+            match (dialect.cvar(name.as_str()), args.get(1)) {
+                (None, _) => {
+                    warnings.push(format!("line {}: unknown cvar {}", i + 1, name));
+                }
+                (Some(Cvar::Unsupported), _) => {
+                    warnings.push(format!("line {}: {} is not supported", i + 1, name));
+                }
+                (Some(cvar), Some(value)) => match value.parse::<f32>() {
+                    Ok(value) if cvar.is_speed() && value <= 0.0 => {
+                        warnings.push(format!("line {}: {} must be positive", i + 1, name));
+                    }
+                    Ok(value) if value.is_finite() => values.push((cvar, value)),
+                    _ => warnings.push(format!("line {}: invalid value for {}", i + 1, name)),
+                },
+                (Some(_), None) => {
+                    warnings.push(format!("line {}: no value for {}", i + 1, name));
+                }
+            }
+        }
+    }
+
+    // later values override earlier ones, as when a config is executed
+    let get = |cvar: Cvar| values.iter().rev().find(|(other, _)| *other == cvar).map(|(_, value)| *value);
+
+    // accelerations are multiples of the target speed, so are applied after
+    // rescaling every speed to match the new maximum
+    let ground_speed = kinematics.move_ground.max_speed;
+    let air_accelerate = kinematics.move_air.accel / ground_speed;
+    if let Some(speed) = get(Cvar::Speed) {
+        let scale = speed / ground_speed;
+        kinematics.move_ground = with_speed(kinematics.move_ground, speed);
+        kinematics.move_walk   = with_speed(kinematics.move_walk  , kinematics.move_walk  .max_speed * scale);
+        kinematics.move_crouch = with_speed(kinematics.move_crouch, kinematics.move_crouch.max_speed * scale);
+        kinematics.water.movement = with_speed(kinematics.water.movement, kinematics.water.movement.max_speed * scale);
+        kinematics.move_air.accel = air_accelerate * speed;
+        // Quake 3 caps air acceleration at the full speed, rather than at a
+        // fixed 30 units as in QuakeWorld and Source
+        if dialect == Dialect::Quake3 {
+            kinematics.move_air.max_speed = speed;
+        }
+    }
+    let speed = kinematics.move_ground.max_speed;
+    if let Some(accelerate) = get(Cvar::Accelerate) {
+        kinematics.move_ground.accel = accelerate * speed;
+        kinematics.move_walk  .accel = accelerate * kinematics.move_walk  .max_speed;
+        kinematics.move_crouch.accel = accelerate * kinematics.move_crouch.max_speed;
+        if dialect == Dialect::QuakeWorld {
+            kinematics.move_air.accel = accelerate * speed;
+        }
+    }
+    if let Some(air_accelerate) = get(Cvar::AirAccelerate) {
+        kinematics.move_air.accel = air_accelerate * speed;
+    }
+    if let Some(air_max_speed) = get(Cvar::AirMaxSpeed) {
+        kinematics.move_air.max_speed = air_max_speed;
+    }
+    if let Some(water_accelerate) = get(Cvar::WaterAccelerate) {
+        kinematics.water.movement.accel = water_accelerate * kinematics.water.movement.max_speed;
+    }
+    if let Some(gravity) = get(Cvar::Gravity) {
+        kinematics.gravity = gravity;
+    }
+    if let Some(friction) = get(Cvar::Friction) {
+        kinematics.friction.friction = friction;
+    }
+    if let Some(water_friction) = get(Cvar::WaterFriction) {
+        kinematics.water.friction = water_friction;
+    }
+    if let Some(stop_speed) = get(Cvar::StopSpeed) {
+        kinematics.friction.stall_speed = stop_speed;
+    }
+    if let Some(max_velocity) = get(Cvar::MaxVelocity) {
+        kinematics.max_velocity = Some(max_velocity);
+    }
+    if let Some(edge_friction) = get(Cvar::EdgeFriction) {
+        kinematics.edge_friction = Some(edge_friction);
+    }
+    if let Some(jump_impulse) = get(Cvar::JumpImpulse) {
+        kinematics.jump_impulse = jump_impulse;
+    }
+    if let Some(auto_hop) = get(Cvar::AutoHop) {
+        kinematics.jump_mode = if auto_hop != 0.0 { JumpMode::Hold } else { JumpMode::RequireRelease };
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::player::{MOVE_SOURCE_LIKE, MOVE_VQ3_LIKE};
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.001, "{} != {}", a, b);
+    }
+
+    fn apply(dialect: Dialect, cfg: &str) -> (Kinematics, Vec<String>) {
+        let mut kinematics = MOVE_VQ3_LIKE;
+        let warnings = apply_cfg(dialect, cfg, &mut kinematics);
+        (kinematics, warnings)
+    }
+
+    #[test]
+    fn tokenize_commands() {
+        assert_eq!(tokenize("seta g_speed \"400\""), vec![vec!["seta", "g_speed", "400"]]);
+        assert_eq!(tokenize("  sv_gravity\t600  "), vec![vec!["sv_gravity", "600"]]);
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn tokenize_quotes() {
+        // quotes keep spaces, separators and comments
+        assert_eq!(tokenize("echo \"a b; c // d\""), vec![vec!["echo", "a b; c // d"]]);
+        assert_eq!(tokenize("echo \"\""), vec![vec!["echo", ""]]);
+        // and end a token, even without whitespace
+        assert_eq!(tokenize("echo a\"b\"c"), vec![vec!["echo", "a", "b", "c"]]);
+        // an unterminated quote runs to the end of the line
+        assert_eq!(tokenize("echo \"a b"), vec![vec!["echo", "a b"]]);
+    }
+
+    #[test]
+    fn tokenize_comments() {
+        assert_eq!(tokenize("sv_gravity 600 // lower"), vec![vec!["sv_gravity", "600"]]);
+        assert_eq!(tokenize("sv_gravity 600// lower"), vec![vec!["sv_gravity", "600"]]);
+        assert!(tokenize("// sv_gravity 600").is_empty());
+        // a single slash is part of a token
+        assert_eq!(tokenize("exec cfg/a.cfg"), vec![vec!["exec", "cfg/a.cfg"]]);
+    }
+
+    #[test]
+    fn tokenize_separators() {
+        assert_eq!(tokenize("sv_gravity 600; sv_friction 4;sv_stopspeed 75"), vec![
+            vec!["sv_gravity", "600"],
+            vec!["sv_friction", "4"],
+            vec!["sv_stopspeed", "75"],
+        ]);
+        assert!(tokenize(" ; ;").is_empty());
+    }
+
+    #[test]
+    fn quake3_cvars() {
+        let (kinematics, warnings) = apply(Dialect::Quake3, "seta g_speed 400\nset pm_airaccelerate 2\npm_jumpvelocity 300\nsetu g_gravity 700");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_near(kinematics.move_ground.max_speed, 400.0);
+        // pm_accelerate is unchanged, so still 10 times the speed
+        assert_near(kinematics.move_ground.accel, 4000.0);
+        assert_near(kinematics.move_walk.max_speed, 200.0);
+        assert_near(kinematics.move_air.max_speed, 400.0);
+        assert_near(kinematics.move_air.accel, 800.0);
+        assert_near(kinematics.jump_impulse, 300.0);
+        assert_near(kinematics.gravity, 700.0);
+    }
+
+    #[test]
+    fn quakeworld_cvars() {
+        let (kinematics, warnings) = apply(Dialect::QuakeWorld, "sv_maxspeed 300\nsv_accelerate 8\nsv_airaccelerate 100\nedgefriction 2");
+        assert_eq!(warnings, vec!["line 3: sv_airaccelerate is not supported"]);
+        assert_near(kinematics.move_ground.max_speed, 300.0);
+        assert_near(kinematics.move_ground.accel, 2400.0);
+        // air acceleration follows sv_accelerate, while air speed isn't rescaled
+        assert_near(kinematics.move_air.accel, 2400.0);
+        assert_near(kinematics.move_air.max_speed, 320.0);
+        assert_eq!(kinematics.edge_friction, Some(2.0));
+    }
+
+    #[test]
+    fn source_cvars() {
+        let (kinematics, warnings) = apply(Dialect::Source, "sv_airaccelerate 150; sv_maxvelocity 3500; sv_autobunnyhopping 1");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_near(kinematics.move_air.accel, 150.0 * 320.0);
+        assert_eq!(kinematics.max_velocity, Some(3500.0));
+        assert!(kinematics.jump_mode == JumpMode::Hold);
+    }
+
+    #[test]
+    fn cvar_warnings() {
+        let (kinematics, warnings) = apply(Dialect::Source, "bind space +jump\ng_speed 400\nsv_gravity\nsv_gravity low\nsv_maxspeed 0\nsv_air_max_wishspeed -30");
+        assert_eq!(warnings, vec![
+            "line 2: unknown cvar g_speed",
+            "line 3: no value for sv_gravity",
+            "line 4: invalid value for sv_gravity",
+            "line 5: sv_maxspeed must be positive",
+            "line 6: sv_air_max_wishspeed must be positive",
+        ]);
+        assert_near(kinematics.move_ground.max_speed, 320.0);
+        assert_near(kinematics.move_ground.accel, 3200.0);
+        assert_near(kinematics.move_air.max_speed, 320.0);
+    }
+
+    #[test]
+    fn later_values_override() {
+        let (kinematics, _) = apply(Dialect::Source, "sv_gravity 600\nsv_gravity 700; SV_GRAVITY 750");
+        assert_near(kinematics.gravity, 750.0);
+    }
+
+    #[test]
+    fn source_cvars_round_trip() {
+        let source = MOVE_SOURCE_LIKE;
+        let cfg = format!("sv_gravity {}\nsv_maxspeed {}\nsv_accelerate {}\nsv_airaccelerate {}\nsv_air_max_wishspeed {}\nsv_friction {}\nsv_stopspeed {}\n",
+            source.gravity,
+            source.move_ground.max_speed,
+            source.move_ground.accel / source.move_ground.max_speed,
+            source.move_air.accel / source.move_ground.max_speed,
+            source.move_air.max_speed,
+            source.friction.friction,
+            source.friction.stall_speed);
+        let mut kinematics = MOVE_VQ3_LIKE;
+        assert!(apply_cfg(Dialect::Source, &cfg, &mut kinematics).is_empty());
+        assert_near(kinematics.gravity, source.gravity);
+        assert_near(kinematics.move_ground.max_speed, source.move_ground.max_speed);
+        assert_near(kinematics.move_ground.accel, source.move_ground.accel);
+        assert_near(kinematics.move_air.max_speed, source.move_air.max_speed);
+        assert_near(kinematics.move_air.accel, source.move_air.accel);
+        assert_near(kinematics.friction.friction, source.friction.friction);
+        assert_near(kinematics.friction.stall_speed, source.friction.stall_speed);
+    }
+}
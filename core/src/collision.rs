@@ -0,0 +1,350 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use cgmath::prelude::*;
+
+use cgmath::{Matrix4, Point3, Rad, Vector3};
+
+use std::cmp::Ordering;
+
+pub const DIST_EPSILON: f32 = 0.031_25;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Hull {
+    pub radius: f32,
+    pub height: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Plane3D {
+    pub norm: Vector3<f32>,
+    pub dist: f32,
+}
+
+impl Plane3D {
+    pub fn new(norm: Vector3<f32>, dist: f32) -> Self {
+        Self{norm, dist}
+    }
+
+    pub fn from_point_and_normal(point: Point3<f32>, norm: Vector3<f32>) -> Self {
+        let norm = norm.normalize();
+        Self{norm, dist: -norm.dot(point.to_vec())}
+    }
+
+    pub fn normalize(self) -> Self {
+        let magnitude = self.norm.magnitude();
+        Self{
+            norm: self.norm / magnitude,
+            dist: self.dist / magnitude
+        }
+    }
+
+    pub fn transform(self, transform: Matrix4<f32>) -> Self {
+        let v = transform.invert().unwrap().transpose() * self.norm.extend(self.dist);
+        Self::new(v.truncate(), v.w).normalize()
+    }
+
+    pub fn dist_to_point(self, point: Point3<f32>) -> f32 {
+        self.norm.dot(point.to_vec()) + self.dist
+    }
+
+    // hulls are upright cylinders with their origin at the center of the base
+    pub fn dist_to_hull(self, origin: Point3<f32>, hull: Hull) -> f32 {
+        self.dist_to_point(origin)
+            - hull.radius * self.norm.xy().magnitude()
+            + (hull.height * self.norm.z).min(0.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Trace {
+    pub fraction: f32,
+    pub norm: Vector3<f32>,
+    pub start_solid: bool,
+    pub all_solid: bool,
+}
+
+impl Default for Trace {
+    fn default() -> Self {
+        Self{
+            fraction: 1.0,
+            norm: Vector3::zero(),
+            start_solid: false,
+            all_solid: false,
+        }
+    }
+}
+
+impl Trace {
+    pub fn is_hit(&self) -> bool {
+        self.fraction < 1.0
+    }
+
+    pub fn end_pos(&self, start: Point3<f32>, end: Point3<f32>) -> Point3<f32> {
+        start + (end - start) * self.fraction
+    }
+
+    pub fn nearest(self, other: Trace) -> Self {
+        let start_solid = self.start_solid || other.start_solid;
+        let all_solid   = self.all_solid   || other.all_solid;
+        let nearest = if other.fraction < self.fraction { other } else { self };
+        Self{start_solid, all_solid, ..nearest}
+    }
+}
+
+pub fn clip_velocity(vel: Vector3<f32>, norm: Vector3<f32>, overbounce: f32) -> Vector3<f32> {
+    let backoff = vel.dot(norm);
+    let backoff = if backoff < 0.0 {
+        backoff * overbounce
+    } else {
+        backoff / overbounce
+    };
+    vel - norm * backoff
+}
+
+// convex volume bounded by outward-facing planes
+#[derive(Clone, Debug)]
+pub struct Brush(Vec<Plane3D>);
+
+impl Brush {
+    pub fn new(planes: Vec<Plane3D>) -> Self {
+        Self(planes)
+    }
+
+    pub fn from_box(min: Point3<f32>, max: Point3<f32>, transform: Matrix4<f32>) -> Self {
+        Self(vec![
+            Plane3D::new(-Vector3::unit_x(),  min.x).transform(transform),
+            Plane3D::new( Vector3::unit_x(), -max.x).transform(transform),
+            Plane3D::new(-Vector3::unit_y(),  min.y).transform(transform),
+            Plane3D::new( Vector3::unit_y(), -max.y).transform(transform),
+            Plane3D::new(-Vector3::unit_z(),  min.z).transform(transform),
+            Plane3D::new( Vector3::unit_z(), -max.z).transform(transform),
+        ])
+    }
+
+    // wedge rising along +y, from min.z at min.y to max.z at max.y
+    pub fn from_ramp(min: Point3<f32>, max: Point3<f32>, transform: Matrix4<f32>) -> Self {
+        let slope = Vector3::new(0.0, min.z - max.z, max.y - min.y);
+        Self(vec![
+            Plane3D::new(-Vector3::unit_x(),  min.x).transform(transform),
+            Plane3D::new( Vector3::unit_x(), -max.x).transform(transform),
+            Plane3D::new( Vector3::unit_y(), -max.y).transform(transform),
+            Plane3D::new(-Vector3::unit_z(),  min.z).transform(transform),
+            Plane3D::from_point_and_normal(min, slope).transform(transform),
+        ])
+    }
+
+    pub fn touches(&self, origin: Point3<f32>, hull: Hull) -> bool {
+        let Self(planes) = &self;
+        planes.iter().all(|plane| plane.dist_to_hull(origin, hull) < 0.0)
+    }
+
+    pub fn trace(&self, start: Point3<f32>, end: Point3<f32>, hull: Hull) -> Trace {
+        let Self(planes) = &self;
+        let mut enter_fraction = -1.0f32;
+        let mut leave_fraction =  1.0f32;
+        let mut norm = Vector3::zero();
+        let mut start_out = false;
+        let mut end_out = false;
+        for plane in planes {
+            let d1 = plane.dist_to_hull(start, hull);
+            let d2 = plane.dist_to_hull(end  , hull);
+            if d1 >= 0.0 { start_out = true; }
+            if d2 >  0.0 { end_out   = true; }
+            if d1 >= 0.0 && (d2 >= DIST_EPSILON || d2 >= d1) {
+                return Trace::default();
+            }
+            if d1 < 0.0 && d2 <= 0.0 {
+                continue;
+            }
+            if d1 > d2 {
+                let f = ((d1 - DIST_EPSILON) / (d1 - d2)).max(0.0);
+                if f > enter_fraction {
+                    enter_fraction = f;
+                    norm = plane.norm;
+                }
+            } else {
+                let f = ((d1 + DIST_EPSILON) / (d1 - d2)).min(1.0);
+                if f < leave_fraction {
+                    leave_fraction = f;
+                }
+            }
+        }
+        if !start_out {
+            Trace{
+                fraction: if end_out { 1.0 } else { 0.0 },
+                start_solid: true,
+                all_solid: !end_out,
+                ..Trace::default()
+            }
+        } else if enter_fraction < leave_fraction && enter_fraction > -1.0 {
+            Trace{
+                fraction: enter_fraction.max(0.0),
+                norm,
+                ..Trace::default()
+            }
+        } else {
+            Trace::default()
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Contents {
+    Water,
+    Ladder,
+}
+
+// non-solid volume which changes how players move inside it
+#[derive(Clone, Debug)]
+pub struct Volume {
+    pub brush: Brush,
+    pub contents: Contents,
+}
+
+impl Volume {
+    pub fn new(brush: Brush, contents: Contents) -> Self {
+        Self{brush, contents}
+    }
+
+    pub fn touches(&self, origin: Point3<f32>, hull: Hull, contents: Contents) -> bool {
+        self.contents == contents && self.brush.touches(origin, hull)
+    }
+}
+
+pub trait Geometry {
+    fn trace(&self, start: Point3<f32>, end: Point3<f32>, hull: Hull) -> Trace;
+
+    fn touches(&self, _origin: Point3<f32>, _hull: Hull, _contents: Contents) -> bool {
+        false
+    }
+
+    // probes horizontally in eight directions for the nearest surface at
+    // least as steep as max_norm_z, returning its normal
+    fn nearest_wall(&self, pos: Point3<f32>, hull: Hull, dist: f32, max_norm_z: f32) -> Option<Vector3<f32>> {
+        (0..8)
+            .map(|i| {
+                let (s, c) = (Rad::<f32>::full_turn() * i as f32 / 8.0).sin_cos();
+                let end = pos + Vector3::new(c, s, 0.0) * dist;
+                self.trace(pos, end, hull)
+            })
+            .filter(|trace| trace.is_hit() && trace.norm.z < max_norm_z)
+            .min_by(|lhs, rhs| lhs.fraction.partial_cmp(&rhs.fraction).unwrap_or(Ordering::Equal))
+            .map(|trace| trace.norm)
+    }
+}
+
+impl Geometry for [Brush] {
+    fn trace(&self, start: Point3<f32>, end: Point3<f32>, hull: Hull) -> Trace {
+        self.iter().fold(Trace::default(), |nearest, brush| {
+            nearest.nearest(brush.trace(start, end, hull))
+        })
+    }
+}
+
+impl Geometry for [Volume] {
+    fn trace(&self, _start: Point3<f32>, _end: Point3<f32>, _hull: Hull) -> Trace {
+        Trace::default()
+    }
+
+    fn touches(&self, origin: Point3<f32>, hull: Hull, contents: Contents) -> bool {
+        self.iter().any(|volume| volume.touches(origin, hull, contents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::SquareMatrix;
+
+    const POINT: Hull = Hull{radius: 0.0, height: 0.0};
+
+    fn assert_near(a: f32, b: f32, tolerance: f32) {
+        assert!((a - b).abs() <= tolerance, "{} != {}", a, b);
+    }
+
+    // 45 degrees, rising 64 units from y = 0 to y = 64
+    fn ramp() -> Brush {
+        Brush::from_ramp(Point3::new(-64.0, 0.0, 0.0), Point3::new(64.0, 64.0, 64.0), Matrix4::identity())
+    }
+
+    // 16 units high, starting at y = 16
+    fn step() -> Brush {
+        Brush::from_box(Point3::new(-64.0, 16.0, 0.0), Point3::new(64.0, 80.0, 16.0), Matrix4::identity())
+    }
+
+    #[test]
+    fn clip_velocity_along_slope() {
+        let norm = Vector3::new(0.0, -1.0, 1.0).normalize();
+        let vel = clip_velocity(Vector3::new(0.0, 0.0, -100.0), norm, 1.001);
+        // just off the slope, by the overbounce
+        assert_near(vel.dot(norm), 100.0 * 0.001 / 2.0f32.sqrt(), 0.0001);
+        assert!(vel.y < 0.0);
+
+        // moving away, only the smaller share is removed
+        let vel = clip_velocity(norm * 100.0, norm, 1.001);
+        assert_near(vel.dot(norm), 100.0 - 100.0 / 1.001, 0.0001);
+    }
+
+    #[test]
+    fn trace_onto_slope() {
+        let start = Point3::new(0.0, 32.0, 100.0);
+        let end = Point3::new(0.0, 32.0, 0.0);
+        let trace = ramp().trace(start, end, POINT);
+        assert!(trace.is_hit());
+        assert!(!trace.start_solid);
+        // stopping DIST_EPSILON short of the surface at z = 32
+        let pos = trace.end_pos(start, end);
+        assert_near(pos.z, 32.0 + DIST_EPSILON * 2.0f32.sqrt(), 0.001);
+        assert_near(trace.norm.y, -0.5f32.sqrt(), 0.0001);
+        assert_near(trace.norm.z,  0.5f32.sqrt(), 0.0001);
+    }
+
+    #[test]
+    fn trace_into_step() {
+        let hull = Hull{radius: 16.0, height: 72.0};
+        let start = Point3::new(0.0, -32.0, 1.0);
+        let end = Point3::new(0.0, 32.0, 1.0);
+        let trace = step().trace(start, end, hull);
+        assert!(trace.is_hit());
+        // the hull's edge stops at the face, y = 16
+        assert_near(trace.end_pos(start, end).y, -DIST_EPSILON, 0.001);
+        assert_near(trace.norm.y, -1.0, 0.0001);
+
+        // over the top of it
+        let start = Point3::new(0.0, -32.0, 17.0);
+        let end = Point3::new(0.0, 32.0, 17.0);
+        assert!(!step().trace(start, end, hull).is_hit());
+
+        // and down onto it
+        let start = Point3::new(0.0, 32.0, 17.0);
+        let end = Point3::new(0.0, 32.0, 0.0);
+        let trace = step().trace(start, end, hull);
+        assert_near(trace.end_pos(start, end).z, 16.0 + DIST_EPSILON, 0.001);
+        assert_near(trace.norm.z, 1.0, 0.0001);
+    }
+
+    #[test]
+    fn trace_from_inside() {
+        let pos = Point3::new(0.0, 48.0, 8.0);
+        let trace = step().trace(pos, pos + Vector3::unit_z(), POINT);
+        assert!(trace.start_solid);
+        assert!(trace.all_solid);
+        assert_near(trace.fraction, 0.0, 0.0);
+    }
+}
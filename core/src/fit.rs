@@ -0,0 +1,313 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+
+use cgmath::prelude::*;
+
+use cgmath::{Deg, Rad, Vector3};
+
+use crate::collision::{Brush, Plane3D};
+use crate::input::KeyState;
+use crate::player::{Kinematics, PlayerState};
+
+// search steps are scaling factors of e^step, halved whenever no parameter
+// improves, down to about 0.1%
+const INITIAL_STEP: f32 = 0.5;
+const MIN_STEP: f32 = 0.001;
+
+#[derive(Copy, Clone)]
+pub struct Sample {
+    pub time_s: f32,
+    pub speed: f32,
+    pub keys: KeyState,
+    // only changes in yaw matter, so any game's convention works as long as
+    // yaw increases to the left, as in Quake
+    pub yaw: Rad<f32>,
+}
+
+// reads a CSV with a header naming its columns: time (seconds), speed, yaw
+// (degrees) and, optionally, forward, back, left, right, jump, crouch and
+// walk, which are 0 or 1
+pub fn parse_csv(data: &str) -> Result<Vec<Sample>, String> {
+    let mut lines = data.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let header = lines.next()
+        .ok_or_else(|| "no header".to_string())?
+        .1.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .collect::<Vec<_>>();
+    let column = |name: &str| header.iter().position(|other| other == name);
+    let required = |name: &str| column(name).ok_or(format!("no {} column", name));
+    let time_column  = required("time")?;
+    let speed_column = required("speed")?;
+    let yaw_column   = required("yaw")?;
+
+    let mut samples = vec![];
+    for (i, line) in lines {
+        let values = line.split(',')
+            .map(|value| value.trim().parse::<f32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| format!("line {}: invalid number", i + 1))?;
+        let value = |column: usize| values.get(column).copied()
+            .ok_or(format!("line {}: missing column", i + 1));
+        let key = |name: &str| column(name).and_then(|column| values.get(column)).is_some_and(|&value| value != 0.0);
+        samples.push(Sample{
+            time_s: value(time_column)?,
+            speed: value(speed_column)?,
+            keys: KeyState{
+                key_w: key("forward"),
+                key_s: key("back"),
+                key_a: key("left"),
+                key_d: key("right"),
+                key_c: key("crouch"),
+                shift: key("walk"),
+                space: key("jump"),
+                ..KeyState::default()
+            },
+            yaw: Deg(value(yaw_column)?).into(),
+        });
+    }
+    if samples.len() < 2 {
+        return Err("at least two samples are needed".to_string());
+    }
+    Ok(samples)
+}
+
+// root-mean-square error between the trace and a run on flat ground with its
+// inputs, starting at its initial speed in the direction of view
+pub fn trace_error(kinematics: &Kinematics, samples: &[Sample], tick_s: f32) -> f32 {
+    let floor = [Brush::new(vec![Plane3D::new(Vector3::unit_z(), 0.0)])];
+    let mut player = PlayerState::default();
+    player.dir.0 = samples[0].yaw;
+    let forward = KeyState{key_w: true, ..KeyState::default()};
+    player.vel = (player.wish_dir(forward, Rad::zero(), Rad::zero()) * samples[0].speed).extend(0.0);
+
+    let mut sum = 0.0;
+    let mut time_s = samples[0].time_s;
+    for window in samples.windows(2) {
+        let (start, end) = (window[0], window[1]);
+        let turn = (end.yaw - start.yaw).normalize_signed();
+        // half a tick of slack, so rounding doesn't drop ticks
+        while time_s + tick_s / 2.0 < end.time_s {
+            let u = (time_s - start.time_s) / (end.time_s - start.time_s);
+            player.dir.0 = start.yaw + turn * u;
+            player.sim_kinematics(kinematics, tick_s, start.keys, &floor[..]);
+            time_s += tick_s;
+        }
+        let error = player.vel.xy().magnitude() - end.speed;
+        sum += error * error;
+    }
+    (sum / (samples.len() - 1) as f32).sqrt()
+}
+
+pub struct Param {
+    pub name: &'static str,
+    get: fn(&Kinematics) -> f32,
+    set: fn(&mut Kinematics, f32),
+    // searched from at least this, as scaling can't move a parameter off 0
+    min: f32,
+    // traces start on flat ground, so this only matters once they jump
+    needs_jump: bool,
+}
+
+impl Param {
+    pub fn get(&self, kinematics: &Kinematics) -> f32 {
+        (self.get)(kinematics)
+    }
+}
+
+pub const PARAMS: &[Param] = &[
+    Param{
+        name: "gravity",
+        get: |k| k.gravity,
+        set: |k, value| k.gravity = value,
+        min: 10.0,
+        needs_jump: true,
+    },
+    Param{
+        name: "friction",
+        get: |k| k.friction.friction,
+        set: |k, value| k.friction.friction = value,
+        min: 0.1,
+        needs_jump: false,
+    },
+    Param{
+        name: "stall speed",
+        get: |k| k.friction.stall_speed,
+        set: |k, value| k.friction.stall_speed = value,
+        min: 1.0,
+        needs_jump: false,
+    },
+    // walking and crouching keep their ratio to running, or with no running
+    // accel to scale, get the same multiple of their speed
+    Param{
+        name: "ground accel",
+        get: |k| k.move_ground.accel,
+        set: |k, value| {
+            if k.move_ground.accel > 0.0 {
+                let scale = value / k.move_ground.accel;
+                k.move_walk  .accel *= scale;
+                k.move_crouch.accel *= scale;
+            } else {
+                let accelerate = value / k.move_ground.max_speed;
+                k.move_walk  .accel = accelerate * k.move_walk  .max_speed;
+                k.move_crouch.accel = accelerate * k.move_crouch.max_speed;
+            }
+            k.move_ground.accel = value;
+        },
+        min: 10.0,
+        needs_jump: false,
+    },
+    Param{
+        name: "air accel",
+        get: |k| k.move_air.accel,
+        set: |k, value| k.move_air.accel = value,
+        min: 1.0,
+        needs_jump: false,
+    },
+];
+
+// a pattern search, scaling one parameter at a time, starting from base;
+// returns the best kinematics found and their error, leaving gravity as it
+// is for traces which never jump
+pub fn fit(base: &Kinematics, samples: &[Sample], tick_s: f32, max_iterations: usize) -> (Kinematics, f32) {
+    let mut best = base.clone();
+    let mut best_error = trace_error(&best, samples, tick_s);
+    let mut step = INITIAL_STEP;
+    let has_jump = samples.iter().any(|sample| sample.keys.space);
+    for _ in 0..max_iterations {
+        let mut next = None;
+        for param in PARAMS.iter().filter(|param| has_jump || !param.needs_jump) {
+            for &scale in &[step.exp(), (-step).exp()] {
+                let mut candidate = best.clone();
+                (param.set)(&mut candidate, param.get(&best).max(param.min) * scale);
+                let error = trace_error(&candidate, samples, tick_s);
+                if error < next.as_ref().map_or(best_error, |(_, error)| *error) {
+                    next = Some((candidate, error));
+                }
+            }
+        }
+        if let Some((candidate, error)) = next {
+            best = candidate;
+            best_error = error;
+        } else {
+            step /= 2.0;
+            if step < MIN_STEP {
+                break;
+            }
+        }
+    }
+    (best, best_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::player::MOVE_VQ3_LIKE;
+
+    const TICK_S: f32 = 0.008;
+
+    // a sample every 5 ticks: running, coasting to a stop, running again,
+    // then hopping while strafing right
+    fn inputs() -> Vec<Sample> {
+        let run    = KeyState{key_w: true, ..KeyState::default()};
+        let strafe = KeyState{key_w: true, key_d: true, space: true, ..KeyState::default()};
+        (0..=100)
+            .map(|i| {
+                let time_s = i as f32 * 5.0 * TICK_S;
+                let (keys, yaw) = match time_s {
+                    t if t < 1.0 => (run, 0.0),
+                    t if t < 1.6 => (KeyState::default(), 0.0),
+                    t if t < 2.4 => (run, 0.0),
+                    t => (strafe, -90.0 * (t - 2.4)),
+                };
+                Sample{time_s, speed: 0.0, keys, yaw: Deg(yaw).into()}
+            })
+            .collect()
+    }
+
+    // fills in speeds by running a trace's inputs as trace_error does
+    fn record(kinematics: &Kinematics, samples: &mut [Sample]) {
+        let floor = [Brush::new(vec![Plane3D::new(Vector3::unit_z(), 0.0)])];
+        let mut player = PlayerState::default();
+        player.dir.0 = samples[0].yaw;
+        let mut time_s = samples[0].time_s;
+        for i in 1..samples.len() {
+            let (start, end) = (samples[i - 1], samples[i]);
+            let turn = (end.yaw - start.yaw).normalize_signed();
+            while time_s + TICK_S / 2.0 < end.time_s {
+                let u = (time_s - start.time_s) / (end.time_s - start.time_s);
+                player.dir.0 = start.yaw + turn * u;
+                player.sim_kinematics(kinematics, TICK_S, start.keys, &floor[..]);
+                time_s += TICK_S;
+            }
+            samples[i].speed = player.vel.xy().magnitude();
+        }
+    }
+
+    fn assert_near(a: f32, b: f32, name: &str) {
+        assert!((a / b - 1.0).abs() < 0.02, "{}: {} != {}", name, a, b);
+    }
+
+    #[test]
+    fn parse() {
+        let samples = parse_csv("Time, Speed, Yaw, Jump\n0, 320, 90, 0\n\n0.5, 400, 45, 1\n").expect("failed to parse");
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].speed, 400.0);
+        assert_eq!(samples[1].yaw, Deg(45.0).into());
+        assert!(samples[1].keys.space && !samples[1].keys.key_w);
+        assert!(parse_csv("time, speed\n0, 320\n1, 320").is_err());
+        assert!(parse_csv("time, speed, yaw\n0, 320, x\n1, 320, 0").is_err());
+    }
+
+    #[test]
+    fn recovers_kinematics() {
+        let mut target = MOVE_VQ3_LIKE;
+        target.friction.friction = 5.0;
+        target.friction.stall_speed = 80.0;
+        target.move_ground.accel = 12.0 * 320.0;
+        target.move_air.accel = 1.5 * 320.0;
+        let mut samples = inputs();
+        record(&target, &mut samples);
+        assert!(trace_error(&target, &samples, TICK_S) < 0.001);
+
+        let (fitted, error) = fit(&MOVE_VQ3_LIKE, &samples, TICK_S, 500);
+        assert!(error < 1.0, "error {}", error);
+        // hopping without landing friction, gravity barely changes speed
+        for param in PARAMS.iter().filter(|param| !param.needs_jump) {
+            assert_near(param.get(&fitted), param.get(&target), param.name);
+        }
+    }
+
+    #[test]
+    fn moves_off_zero() {
+        let mut target = MOVE_VQ3_LIKE;
+        target.move_air.accel = 0.5 * 320.0;
+        let mut samples = inputs();
+        record(&target, &mut samples);
+
+        let mut base = MOVE_VQ3_LIKE;
+        base.move_air.accel = 0.0;
+        base.move_ground.accel = 0.0;
+        let (fitted, _) = fit(&base, &samples, TICK_S, 500);
+        assert_near(fitted.move_air.accel, target.move_air.accel, "air accel");
+        assert_near(fitted.move_ground.accel, target.move_ground.accel, "ground accel");
+        assert!(fitted.move_walk.accel.is_finite());
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::ops;
+
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub struct KeyState {
+    pub key_w: bool,
+    pub key_a: bool,
+    pub key_s: bool,
+    pub key_d: bool,
+    pub key_f: bool,
+    pub key_c: bool,
+    pub key_e: bool,
+    pub shift: bool,
+    pub mouse_left: bool,
+    pub space: bool,
+}
+
+pub const KEYS_DEFAULT: KeyState = KeyState{
+    key_w: false,
+    key_a: false,
+    key_s: false,
+    key_d: false,
+    key_f: false,
+    key_c: false,
+    key_e: false,
+    shift: false,
+    mouse_left: false,
+    space: false,
+};
+
+impl KeyState {
+    pub fn is_side_strafe(self) -> bool {
+         (self.key_a || self.key_d) &&
+        !(self.key_w || self.key_s)
+    }
+
+    pub fn is_forward_strafe(self) -> bool {
+         (self.key_w || self.key_s) &&
+        !(self.key_a || self.key_d)
+    }
+
+    pub fn pressed(self, previous: KeyState) -> KeyState {
+        self & !previous
+    }
+
+    pub fn released(self, previous: KeyState) -> KeyState {
+        !self & previous
+    }
+}
+
+impl ops::Not for KeyState {
+    type Output = KeyState;
+    fn not(self) -> KeyState {
+        KeyState{
+            key_w: !self.key_w,
+            key_a: !self.key_a,
+            key_s: !self.key_s,
+            key_d: !self.key_d,
+            key_f: !self.key_f,
+            key_c: !self.key_c,
+            key_e: !self.key_e,
+            shift: !self.shift,
+            mouse_left: !self.mouse_left,
+            space: !self.space,
+        }
+    }
+}
+
+impl ops::BitAnd for KeyState {
+    type Output = KeyState;
+    fn bitand(self, other: KeyState) -> KeyState {
+        KeyState{
+            key_w: self.key_w & other.key_w,
+            key_a: self.key_a & other.key_a,
+            key_s: self.key_s & other.key_s,
+            key_d: self.key_d & other.key_d,
+            key_f: self.key_f & other.key_f,
+            key_c: self.key_c & other.key_c,
+            key_e: self.key_e & other.key_e,
+            shift: self.shift & other.shift,
+            mouse_left: self.mouse_left & other.mouse_left,
+            space: self.space & other.space,
+        }
+    }
+}
+
+impl ops::BitOr for KeyState {
+    type Output = KeyState;
+    fn bitor(self, other: KeyState) -> KeyState {
+        KeyState{
+            key_w: self.key_w | other.key_w,
+            key_a: self.key_a | other.key_a,
+            key_s: self.key_s | other.key_s,
+            key_d: self.key_d | other.key_d,
+            key_f: self.key_f | other.key_f,
+            key_c: self.key_c | other.key_c,
+            key_e: self.key_e | other.key_e,
+            shift: self.shift | other.shift,
+            mouse_left: self.mouse_left | other.mouse_left,
+            space: self.space | other.space,
+        }
+    }
+}
\ No newline at end of file
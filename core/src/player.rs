@@ -0,0 +1,2189 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use cgmath::prelude::*;
+
+use cgmath::{
+    Deg,
+    Matrix3,
+    Matrix4,
+    Point3,
+    Rad,
+    Vector2,
+    Vector3,
+};
+
+use crate::collision::{clip_velocity, Contents, Geometry, Hull};
+use crate::input::KeyState;
+
+use serde::{Serialize, Deserialize};
+
+pub const PLAYER_EYELEVEL: f32 = 64.0;
+pub const PLAYER_RADIUS: f32 = 16.0;
+pub const PLAYER_HEIGHT: f32 = 72.0;
+pub const PLAYER_HULL: Hull = Hull{
+    radius: PLAYER_RADIUS,
+    height: PLAYER_HEIGHT,
+};
+pub const PLAYER_CROUCH_EYELEVEL: f32 = 28.0;
+pub const PLAYER_CROUCH_HEIGHT: f32 = 36.0;
+pub const PLAYER_CROUCH_HULL: Hull = Hull{
+    radius: PLAYER_RADIUS,
+    height: PLAYER_CROUCH_HEIGHT,
+};
+pub const JUMP_GROUND_DIST: f32 = 0.25;
+pub const MIN_WALK_NORMAL: f32 = 0.7;
+pub const STEP_SIZE: f32 = 18.0;
+pub const WALL_JUMP_DIST: f32 = 12.0;
+pub const WATER_JUMP_DIST: f32 = 30.0;
+
+// push velocity slightly away from surfaces so moves parallel to a plane
+// don't get caught on it due to rounding
+const OVERCLIP: f32 = 1.001;
+const MAX_BUMPS: usize = 4;
+const MAX_CLIP_PLANES: usize = 5;
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Friction {
+    pub stall_speed: f32,
+    pub friction: f32,
+}
+
+impl Friction {
+    fn sim(self, vel: &mut Vector3<f32>, dt: f32) {
+        let speed0 = vel.xy().magnitude();
+        if speed0 > 0.0001 {
+            let speed1 = (speed0 - speed0.max(self.stall_speed) * self.friction * dt).max(0.0);
+            vel.x *= speed1 / speed0;
+            vel.y *= speed1 / speed0;
+        }
+    }
+
+    // as sim, but slowing vertical movement too, e.g. in water or on ladders
+    fn sim_3d(self, vel: &mut Vector3<f32>, dt: f32) {
+        let speed0 = vel.magnitude();
+        if speed0 > 0.0001 {
+            let speed1 = (speed0 - speed0.max(self.stall_speed) * self.friction * dt).max(0.0);
+            *vel *= speed1 / speed0;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Movement {
+    pub max_speed: f32,
+    pub accel: f32,
+}
+
+impl Movement {
+    fn sim(self, vel: &mut Vector3<f32>, dt: f32, wish_dir: Vector2<f32>) {
+        let add_speed = (self.max_speed - vel.xy().dot(wish_dir)).max(0.0);
+        let dv = wish_dir.extend(0.0) * (self.accel * dt).min(add_speed);
+        *vel += dv;
+    }
+
+    // as sim, in any direction and toward any speed up to max_speed
+    fn sim_3d(self, vel: &mut Vector3<f32>, dt: f32, wish_vel: Vector3<f32>) {
+        let wish_speed = wish_vel.magnitude().min(self.max_speed);
+        if wish_speed < 0.0001 {
+            return;
+        }
+        let wish_dir = wish_vel.normalize();
+        let add_speed = (wish_speed - vel.dot(wish_dir)).max(0.0);
+        let accel = self.accel * wish_speed / self.max_speed;
+        *vel += wish_dir * (accel * dt).min(add_speed);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Integrator {
+    // full gravity step before moving
+    Euler,
+    // half of gravity before moving and half after, as in Source/GoldSrc
+    HalfStepGravity,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JumpMode {
+    // jump whenever grounded with jump held
+    Hold,
+    // jump must be released between jumps, as in Quake 3 and Source
+    RequireRelease,
+    // as RequireRelease, but a press up to this many seconds before landing still jumps
+    Buffer(f32),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LandingFriction {
+    // friction applies on the tick of landing, even when jumping, as in Source
+    Immediate,
+    // friction is skipped for this many ticks after landing; jumping always
+    // skips it, so 0 matches Quake 3
+    FrictionFree(u32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stance {
+    Standing,
+    Walking,
+    Crouching,
+    // crouched with reduced friction shortly after landing or crouching
+    Sliding,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct CrouchSlide {
+    pub friction: f32,
+    pub duration_s: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BhopCap {
+    None,
+    // ground speed is clamped when jumping
+    Clamp(f32),
+    // ground speed above the cap is scaled by factor when jumping
+    Scale{cap: f32, factor: f32},
+    // HL2's jump: boosted along the view direction by a fraction of ground
+    // speed, but only up to that fraction over ground speed, so faster than
+    // that the boost turns negative, which accelerates players hopping
+    // backwards; speed isn't clamped first
+    Abh{boost: f32},
+}
+
+impl BhopCap {
+    fn sim(self, vel: &mut Vector3<f32>, forward: Vector2<f32>, forward_move: f32, max_speed: f32) {
+        let speed = vel.xy().magnitude();
+        let capped = match self {
+            BhopCap::None => return,
+            BhopCap::Clamp(cap) => speed.min(cap),
+            BhopCap::Scale{cap, factor} if speed > cap => cap + (speed - cap) * factor,
+            BhopCap::Scale{..} => speed,
+            BhopCap::Abh{boost} => {
+                let limit = max_speed * (1.0 + boost);
+                let mut add_speed = (forward_move * max_speed * boost).abs();
+                let new_speed = add_speed + speed;
+                if new_speed > limit {
+                    add_speed -= new_speed - limit;
+                }
+                if forward_move < 0.0 {
+                    add_speed = -add_speed;
+                }
+                *vel += forward.extend(0.0) * add_speed;
+                return;
+            }
+        };
+        if speed > 0.0001 {
+            vel.x *= capped / speed;
+            vel.y *= capped / speed;
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct DoubleJump {
+    pub window_s: f32,
+    pub impulse: f32,
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Dash {
+    // horizontal speed is raised to at least this much
+    pub speed: f32,
+    pub upward_speed: f32,
+    pub cooldown_s: f32,
+}
+
+impl Dash {
+    fn sim(self, vel: &mut Vector3<f32>, dir: Vector2<f32>) {
+        let speed = vel.xy().magnitude().max(self.speed);
+        vel.x = dir.x * speed;
+        vel.y = dir.y * speed;
+        vel.z = vel.z.max(self.upward_speed);
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct WallJump {
+    // how sharply horizontal velocity is turned away from the wall
+    pub bounce: f32,
+    pub min_speed: f32,
+    pub upward_speed: f32,
+    pub cooldown_s: f32,
+}
+
+impl WallJump {
+    fn sim(self, vel: &mut Vector3<f32>, norm: Vector3<f32>) {
+        let speed = vel.xy().magnitude().max(self.min_speed);
+        let dir = clip_velocity(*vel, norm, OVERCLIP).xy() + norm.xy() * speed * self.bounce;
+        if dir.magnitude2() > 0.0 {
+            let dir = dir.normalize();
+            vel.x = dir.x * speed;
+            vel.y = dir.y * speed;
+        }
+        vel.z = vel.z.max(self.upward_speed);
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Water {
+    pub movement: Movement,
+    // scaled by how deep the player is, as with pm_waterfriction
+    pub friction: f32,
+    // drift downward while not swimming
+    pub sink_speed: f32,
+    // climbing out onto a ledge
+    pub jump_speed: f32,
+    pub jump_upward_speed: f32,
+    pub jump_duration_s: f32,
+}
+
+impl Water {
+    fn sim_friction(self, vel: &mut Vector3<f32>, dt: f32, water_level: u8) {
+        let friction = Friction{
+            stall_speed: 0.0,
+            friction: self.friction * f32::from(water_level),
+        };
+        friction.sim_3d(vel, dt);
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Ladder {
+    pub climb_speed: f32,
+    // movement across the ladder, which is very slow in Quake 2
+    pub movement: Movement,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MoveState {
+    Ground,
+    Air,
+    // at least waist deep in water
+    Swim,
+    // climbing out of the water, without control until falling
+    WaterJump,
+    Ladder,
+}
+
+#[derive(Copy, Clone)]
+pub struct Ground {
+    pub norm: Vector3<f32>,
+    pub is_edge: bool,
+    pub stance: Stance,
+    // jumped recently enough for a double jump
+    pub is_double_jump: bool,
+    // ticks since landing, 0 on the tick of landing
+    pub landed_ticks: u32,
+    // recently knocked back, so ground friction and acceleration don't apply
+    pub knockback: bool,
+}
+
+// fields missing from saved presets, e.g. ones added since, come from the default
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Kinematics {
+    pub gravity: f32,
+    pub jump_impulse: f32,
+    // jumping adds to upward velocity, e.g. when running up a ramp, instead
+    // of replacing it
+    pub ramp_jump: bool,
+    // extra impulse for jumping again soon after landing, as in CPMA
+    pub double_jump: Option<DoubleJump>,
+    // applied to ground speed when jumping
+    pub bhop_cap: BhopCap,
+    pub friction: Friction,
+    pub move_ground: Movement,
+    pub move_walk: Movement,
+    pub move_crouch: Movement,
+    pub move_air: Movement,
+    pub move_air_turning: Option<Movement>,
+    pub move_air_stopping: Option<Movement>,
+    pub air_control: Option<f32>,
+    // per-axis clamp, as with sv_maxvelocity
+    pub max_velocity: Option<f32>,
+    // friction multiplier near ledges, as with GoldSrc's sv_edgefriction
+    pub edge_friction: Option<f32>,
+    pub integrator: Integrator,
+    // emulate Quake 3's pmove: gravity is split around the move as with
+    // HalfStepGravity, velocity is snapped to integers every tick, which makes
+    // jump height depend on frame rate, and walking keeps its speed when
+    // clipped to a slope, as in PM_WalkMove
+    pub q3_fidelity: bool,
+    pub jump_mode: JumpMode,
+    pub landing_friction: LandingFriction,
+    pub crouch_slide: Option<CrouchSlide>,
+    pub dash: Option<Dash>,
+    pub wall_jump: Option<WallJump>,
+    pub water: Water,
+    pub ladder: Ladder,
+}
+
+impl Kinematics {
+    // stance is None while airborne
+    pub fn effective_movement(&self, stance: Option<Stance>, is_turning: bool) -> Movement {
+        if let Some(stance) = stance {
+            match stance {
+                Stance::Standing => self.move_ground,
+                Stance::Walking  => self.move_walk,
+                Stance::Crouching | Stance::Sliding => self.move_crouch,
+            }
+        } else {
+            if is_turning {
+                if let Some(move_air_turning) = self.move_air_turning {
+                    return move_air_turning
+                }
+            }
+            self.move_air
+        }
+    }
+
+    // bends velocity toward the wish direction without changing speed
+    fn sim_air_control(air_control: f32, vel: &mut Vector3<f32>, dt: f32, wish_dir: Vector2<f32>) {
+        let speed = vel.xy().magnitude();
+        if speed < 0.0001 {
+            return;
+        }
+        let dir = vel.xy() / speed;
+        let dot = dir.dot(wish_dir);
+        if dot > 0.0 {
+            let k = 32.0 * air_control * dot * dot * dt;
+            let dir = (dir * speed + wish_dir * k).normalize();
+            vel.x = dir.x * speed;
+            vel.y = dir.y * speed;
+        }
+    }
+
+    fn sim_ground_friction(&self, vel: &mut Vector3<f32>, dt: f32, ground: Ground) {
+        if ground.knockback {
+            return;
+        }
+        let mut friction = self.friction;
+        if ground.stance == Stance::Sliding {
+            if let Some(crouch_slide) = self.crouch_slide {
+                friction.friction = crouch_slide.friction;
+            }
+        }
+        if ground.is_edge {
+            if let Some(edge_friction) = self.edge_friction {
+                friction.friction *= edge_friction;
+            }
+        }
+        friction.sim(vel, dt);
+    }
+
+    pub fn sim(&self,
+        vel: &mut Vector3<f32>,
+        dt: f32,
+        wish_dir: Vector2<f32>,
+        forward: Vector2<f32>,
+        mut ground: Option<Ground>,
+        keys: KeyState)
+    {
+        let is_turning = keys.is_side_strafe();
+        let is_forward = keys.is_forward_strafe();
+
+        if keys.space && self.landing_friction == LandingFriction::Immediate {
+            if let Some(ground) = ground.filter(|ground| ground.landed_ticks == 0) {
+                self.sim_ground_friction(vel, dt, ground);
+            }
+        }
+
+        // velocity is already clipped to the ground plane, so jumping while
+        // moving up a ramp can carry that vertical speed into the jump
+        if keys.space {
+            if let Some(ground) = ground {
+                let forward_move = match (keys.key_w, keys.key_s) {
+                    (true, false) =>  1.0,
+                    (false, true) => -1.0,
+                    _ => 0.0,
+                };
+                let max_speed = self.effective_movement(Some(ground.stance), false).max_speed;
+                self.bhop_cap.sim(vel, forward, forward_move, max_speed);
+                vel.z = if self.ramp_jump { vel.z.max(0.0) } else { 0.0 } + self.jump_impulse;
+                if let (Some(double_jump), true) = (self.double_jump, ground.is_double_jump) {
+                    vel.z += double_jump.impulse;
+                }
+            }
+            ground = None;
+        }
+
+        if let Some(ground) = ground {
+            let is_friction_free = match self.landing_friction {
+                LandingFriction::Immediate => false,
+                LandingFriction::FrictionFree(ticks) => ground.landed_ticks < ticks,
+            };
+            if !is_friction_free {
+                self.sim_ground_friction(vel, dt, ground);
+            }
+        }
+
+        let stance = ground
+            .filter(|ground| !ground.knockback)
+            .map(|ground| ground.stance);
+        let mut movement = self.effective_movement(stance, is_turning);
+        if ground.is_none() && !is_turning && vel.xy().dot(wish_dir) < 0.0 {
+            if let Some(move_air_stopping) = self.move_air_stopping {
+                movement = move_air_stopping;
+            }
+        }
+        movement.sim(vel, dt, wish_dir);
+
+        if ground.is_none() && is_forward && wish_dir.magnitude2() > 0.0 {
+            if let Some(air_control) = self.air_control {
+                Self::sim_air_control(air_control, vel, dt, wish_dir);
+            }
+        }
+
+        if let Some(ground) = ground {
+            let speed = vel.magnitude();
+            *vel = clip_velocity(*vel, ground.norm, OVERCLIP);
+            if self.q3_fidelity && vel.magnitude2() > 0.0 {
+                *vel = vel.normalize() * speed;
+            }
+        } else {
+            vel.z -= match self.integrator() {
+                Integrator::Euler           => self.gravity * dt,
+                Integrator::HalfStepGravity => self.gravity * dt * 0.5,
+            };
+        }
+
+        self.clamp_velocity(vel);
+    }
+
+    // as Quake 3's PM_WaterMove, swimming in the view direction without gravity
+    pub fn sim_swim(&self,
+        vel: &mut Vector3<f32>,
+        dt: f32,
+        wish_dir: Vector3<f32>,
+        water_level: u8,
+        ground: Option<Vector3<f32>>)
+    {
+        self.water.sim_friction(vel, dt, water_level);
+
+        let wish_vel = if wish_dir.magnitude2() > 0.0 {
+            wish_dir * self.water.movement.max_speed
+        } else {
+            -Vector3::unit_z() * self.water.sink_speed
+        };
+        self.water.movement.sim_3d(vel, dt, wish_vel);
+
+        // swim along the bottom rather than into it
+        if let Some(norm) = ground {
+            if vel.dot(norm) < 0.0 {
+                *vel = clip_velocity(*vel, norm, OVERCLIP);
+            }
+        }
+
+        self.clamp_velocity(vel);
+    }
+
+    // as Quake 2's ladders, climbing without gravity and sidestepping slowly
+    pub fn sim_ladder(&self, vel: &mut Vector3<f32>, dt: f32, wish_dir: Vector2<f32>, climb: f32) {
+        self.friction.sim_3d(vel, dt);
+
+        let wish_vel = wish_dir.extend(0.0) * self.ladder.movement.max_speed
+                     + Vector3::unit_z() * climb * self.ladder.climb_speed;
+        // accelerate as quickly relative to climbing speed as to sidestepping speed
+        let max_speed = self.ladder.climb_speed.max(self.ladder.movement.max_speed);
+        let movement = Movement{
+            max_speed,
+            accel: self.ladder.movement.accel * max_speed / self.ladder.movement.max_speed,
+        };
+        movement.sim_3d(vel, dt, wish_vel);
+
+        self.clamp_velocity(vel);
+    }
+
+    // as sim, for players standing in shallow water
+    pub fn sim_wading(&self, vel: &mut Vector3<f32>, dt: f32, water_level: u8) {
+        self.water.sim_friction(vel, dt, water_level);
+    }
+
+    // called after moving
+    pub fn sim_finish(&self, vel: &mut Vector3<f32>, dt: f32, is_falling: bool) {
+        if is_falling && self.integrator() == Integrator::HalfStepGravity {
+            vel.z -= self.gravity * dt * 0.5;
+        }
+
+        self.clamp_velocity(vel);
+
+        if self.q3_fidelity {
+            vel.x = vel.x.round();
+            vel.y = vel.y.round();
+            vel.z = vel.z.round();
+        }
+    }
+
+    // Quake 3 moves with the average of the velocities before and after gravity
+    fn integrator(&self) -> Integrator {
+        if self.q3_fidelity {
+            Integrator::HalfStepGravity
+        } else {
+            self.integrator
+        }
+    }
+
+    fn clamp_velocity(&self, vel: &mut Vector3<f32>) {
+        if let Some(max_velocity) = self.max_velocity {
+            vel.x = vel.x.max(-max_velocity).min(max_velocity);
+            vel.y = vel.y.max(-max_velocity).min(max_velocity);
+            vel.z = vel.z.max(-max_velocity).min(max_velocity);
+        }
+    }
+}
+
+impl Default for Kinematics {
+    fn default() -> Self {
+        MOVE_VQ3_LIKE
+    }
+}
+
+pub const MOVE_VQ3_LIKE: Kinematics = Kinematics{
+    gravity: 800.0,
+    jump_impulse: 270.0,
+    ramp_jump: false,
+    double_jump: None,
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 100.0,
+        friction: 6.0,
+    },
+    move_ground: Movement{
+        max_speed: 320.0,
+        accel: 10.0 * 320.0,
+    },
+    move_walk: Movement{
+        max_speed: 160.0,
+        accel: 10.0 * 160.0,
+    },
+    move_crouch: Movement{
+        max_speed: 80.0,
+        accel: 10.0 * 80.0,
+    },
+    move_air: Movement{
+        max_speed: 320.0,
+        accel: 1.0 * 320.0,
+    },
+    move_air_turning: None,
+    move_air_stopping: None,
+    air_control: None,
+    max_velocity: None,
+    edge_friction: None,
+    integrator: Integrator::Euler,
+    q3_fidelity: false,
+    jump_mode: JumpMode::Hold,
+    landing_friction: LandingFriction::FrictionFree(0),
+    crouch_slide: None,
+    dash: None,
+    wall_jump: None,
+    water: Water{
+        movement: Movement{
+            max_speed: 0.5 * 320.0,
+            accel: 4.0 * 0.5 * 320.0,
+        },
+        friction: 1.0,
+        sink_speed: 60.0,
+        jump_speed: 200.0,
+        jump_upward_speed: 350.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 25.0,
+            accel: 10.0 * 25.0,
+        },
+    },
+};
+
+pub const MOVE_QW_LIKE: Kinematics = Kinematics{
+    gravity: 800.0,
+    jump_impulse: 270.0,
+    ramp_jump: true,
+    double_jump: None,
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 100.0,
+        friction: 6.0,
+    },
+    move_ground: Movement{
+        max_speed: 320.0,
+        accel: 10.0 * 320.0,
+    },
+    move_walk: Movement{
+        max_speed: 160.0,
+        accel: 10.0 * 160.0,
+    },
+    move_crouch: Movement{
+        max_speed: 80.0,
+        accel: 10.0 * 80.0,
+    },
+    move_air: Movement{
+        max_speed: 30.0,
+        accel: 10.0 * 320.0,
+    },
+    move_air_turning: None,
+    move_air_stopping: None,
+    air_control: None,
+    max_velocity: None,
+    edge_friction: None,
+    integrator: Integrator::Euler,
+    q3_fidelity: false,
+    jump_mode: JumpMode::Hold,
+    landing_friction: LandingFriction::FrictionFree(0),
+    crouch_slide: None,
+    dash: None,
+    wall_jump: None,
+    water: Water{
+        movement: Movement{
+            max_speed: 0.7 * 320.0,
+            accel: 10.0 * 0.7 * 320.0,
+        },
+        friction: 4.0,
+        sink_speed: 60.0,
+        jump_speed: 50.0,
+        jump_upward_speed: 225.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 25.0,
+            accel: 10.0 * 25.0,
+        },
+    },
+};
+
+pub const MOVE_HYBRID: Kinematics = Kinematics{
+    gravity: 800.0,
+    jump_impulse: 270.0,
+    ramp_jump: false,
+    double_jump: None,
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 100.0,
+        friction: 6.0,
+    },
+    move_ground: Movement{
+        max_speed: 320.0,
+        accel: 10.0 * 320.0,
+    },
+    move_walk: Movement{
+        max_speed: 160.0,
+        accel: 10.0 * 160.0,
+    },
+    move_crouch: Movement{
+        max_speed: 80.0,
+        accel: 10.0 * 80.0,
+    },
+    move_air: Movement{
+        max_speed: 320.0,
+        accel: 1.0 * 320.0,
+    },
+    move_air_turning: Some(Movement{
+        max_speed: 35.0,
+        accel: 2100.0,
+    }),
+    move_air_stopping: None,
+    air_control: None,
+    max_velocity: None,
+    edge_friction: None,
+    integrator: Integrator::Euler,
+    q3_fidelity: false,
+    jump_mode: JumpMode::Hold,
+    landing_friction: LandingFriction::FrictionFree(0),
+    crouch_slide: None,
+    dash: None,
+    wall_jump: None,
+    water: Water{
+        movement: Movement{
+            max_speed: 0.5 * 320.0,
+            accel: 4.0 * 0.5 * 320.0,
+        },
+        friction: 1.0,
+        sink_speed: 60.0,
+        jump_speed: 200.0,
+        jump_upward_speed: 350.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 25.0,
+            accel: 10.0 * 25.0,
+        },
+    },
+};
+
+pub const MOVE_CPMA_LIKE: Kinematics = Kinematics{
+    gravity: 800.0,
+    jump_impulse: 270.0,
+    ramp_jump: true,
+    double_jump: Some(DoubleJump{
+        window_s: 0.4,
+        impulse: 100.0,
+    }),
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 100.0,
+        friction: 8.0,
+    },
+    move_ground: Movement{
+        max_speed: 320.0,
+        accel: 15.0 * 320.0,
+    },
+    move_walk: Movement{
+        max_speed: 160.0,
+        accel: 15.0 * 160.0,
+    },
+    move_crouch: Movement{
+        max_speed: 80.0,
+        accel: 15.0 * 80.0,
+    },
+    move_air: Movement{
+        max_speed: 320.0,
+        accel: 1.0 * 320.0,
+    },
+    move_air_turning: Some(Movement{
+        max_speed: 30.0,
+        accel: 70.0 * 30.0,
+    }),
+    move_air_stopping: Some(Movement{
+        max_speed: 320.0,
+        accel: 2.5 * 320.0,
+    }),
+    air_control: Some(150.0),
+    max_velocity: None,
+    edge_friction: None,
+    integrator: Integrator::Euler,
+    q3_fidelity: false,
+    jump_mode: JumpMode::Hold,
+    landing_friction: LandingFriction::FrictionFree(0),
+    crouch_slide: Some(CrouchSlide{
+        friction: 1.0,
+        duration_s: 0.5,
+    }),
+    dash: None,
+    wall_jump: None,
+    water: Water{
+        movement: Movement{
+            max_speed: 0.5 * 320.0,
+            accel: 4.0 * 0.5 * 320.0,
+        },
+        friction: 1.0,
+        sink_speed: 60.0,
+        jump_speed: 200.0,
+        jump_upward_speed: 350.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 25.0,
+            accel: 10.0 * 25.0,
+        },
+    },
+};
+
+// jump impulse reaches 57 units: sqrt(2 * 800 * 57)
+pub const MOVE_SOURCE_LIKE: Kinematics = Kinematics{
+    gravity: 800.0,
+    jump_impulse: 301.993,
+    ramp_jump: true,
+    double_jump: None,
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 75.0,
+        friction: 4.0,
+    },
+    move_ground: Movement{
+        max_speed: 250.0,
+        accel: 5.0 * 250.0,
+    },
+    move_walk: Movement{
+        max_speed: 130.0,
+        accel: 5.0 * 130.0,
+    },
+    move_crouch: Movement{
+        max_speed: 83.25,
+        accel: 5.0 * 83.25,
+    },
+    move_air: Movement{
+        max_speed: 30.0,
+        accel: 10.0 * 250.0,
+    },
+    move_air_turning: None,
+    move_air_stopping: None,
+    air_control: None,
+    max_velocity: Some(3500.0),
+    edge_friction: None,
+    integrator: Integrator::HalfStepGravity,
+    q3_fidelity: false,
+    jump_mode: JumpMode::RequireRelease,
+    landing_friction: LandingFriction::Immediate,
+    crouch_slide: None,
+    dash: None,
+    wall_jump: None,
+    water: Water{
+        movement: Movement{
+            max_speed: 0.8 * 250.0,
+            accel: 10.0 * 0.8 * 250.0,
+        },
+        friction: 1.0,
+        sink_speed: 60.0,
+        jump_speed: 50.0,
+        jump_upward_speed: 256.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 200.0,
+            accel: 5.0 * 200.0,
+        },
+    },
+};
+
+// gravity-scaled impulses, as with Warsow's GRAVITY_COMPENSATE
+pub const MOVE_WARSOW_LIKE: Kinematics = Kinematics{
+    gravity: 850.0,
+    jump_impulse: 280.0,
+    ramp_jump: true,
+    double_jump: None,
+    bhop_cap: BhopCap::None,
+    friction: Friction{
+        stall_speed: 100.0,
+        friction: 8.0,
+    },
+    move_ground: Movement{
+        max_speed: 320.0,
+        accel: 12.0 * 320.0,
+    },
+    move_walk: Movement{
+        max_speed: 160.0,
+        accel: 12.0 * 160.0,
+    },
+    move_crouch: Movement{
+        max_speed: 100.0,
+        accel: 12.0 * 100.0,
+    },
+    move_air: Movement{
+        max_speed: 320.0,
+        accel: 1.0 * 320.0,
+    },
+    move_air_turning: Some(Movement{
+        max_speed: 30.0,
+        accel: 70.0 * 30.0,
+    }),
+    move_air_stopping: Some(Movement{
+        max_speed: 320.0,
+        accel: 2.5 * 320.0,
+    }),
+    air_control: Some(150.0),
+    max_velocity: None,
+    edge_friction: None,
+    integrator: Integrator::Euler,
+    q3_fidelity: false,
+    jump_mode: JumpMode::Hold,
+    landing_friction: LandingFriction::FrictionFree(0),
+    crouch_slide: Some(CrouchSlide{
+        friction: 1.0,
+        duration_s: 1.5,
+    }),
+    dash: Some(Dash{
+        speed: 450.0,
+        upward_speed: 174.0 * 1.0625,
+        cooldown_s: 1.0,
+    }),
+    wall_jump: Some(WallJump{
+        bounce: 0.3,
+        min_speed: 240.0,
+        upward_speed: 330.0 * 1.0625,
+        cooldown_s: 1.3,
+    }),
+    water: Water{
+        movement: Movement{
+            max_speed: 0.5 * 320.0,
+            accel: 4.0 * 0.5 * 320.0,
+        },
+        friction: 1.0,
+        sink_speed: 60.0,
+        jump_speed: 200.0,
+        jump_upward_speed: 350.0,
+        jump_duration_s: 2.0,
+    },
+    ladder: Ladder{
+        climb_speed: 200.0,
+        movement: Movement{
+            max_speed: 25.0,
+            accel: 10.0 * 25.0,
+        },
+    },
+};
+
+#[derive(Clone)]
+pub struct PlayerState {
+    pub pos: Point3<f32>,
+    pub vel: Vector3<f32>,
+    pub dir: (Rad<f32>, Rad<f32>),
+    pub ground: Option<Vector3<f32>>,
+    // jump has been held since it was last used, as with Quake 3's PMF_JUMP_HELD
+    pub jump_held: bool,
+    // time left for a buffered jump press
+    pub jump_buffer_s: Option<f32>,
+    pub crouched: bool,
+    // time left in a crouch slide
+    pub slide_s: Option<f32>,
+    pub landed_ticks: Option<u32>,
+    // ticks spent on the ground before the last jump, 0 for a perfect hop
+    pub hop_ticks: Option<u32>,
+    pub perfect_hop_streak: u32,
+    // time left for a double jump after jumping, as with CPMA's STAT_JUMPTIME
+    pub double_jump_s: Option<f32>,
+    // special has been held since it was last used
+    pub special_held: bool,
+    pub dash_cooldown_s: Option<f32>,
+    pub wall_jump_cooldown_s: Option<f32>,
+    // time left without ground friction after being knocked back
+    pub knockback_s: Option<f32>,
+    pub move_state: MoveState,
+    // 0 when dry, then submerged to the feet, waist or eyes, as in Quake
+    pub water_level: u8,
+    // time left climbing out of the water
+    pub water_jump_s: Option<f32>,
+    pub water_jump_dir: Vector2<f32>,
+}
+
+impl Default for PlayerState {
+    fn default() -> Self {
+        Self{
+            pos: Point3::new(0.0, 0.0, 0.0),
+            vel: Vector3::new(0.0, 0.0, 0.0),
+            dir: (Rad(0.0), Deg(90.0).into()),
+            ground: None,
+            jump_held: false,
+            jump_buffer_s: None,
+            crouched: false,
+            slide_s: None,
+            landed_ticks: None,
+            hop_ticks: None,
+            perfect_hop_streak: 0,
+            double_jump_s: None,
+            special_held: false,
+            dash_cooldown_s: None,
+            wall_jump_cooldown_s: None,
+            knockback_s: None,
+            move_state: MoveState::Air,
+            water_level: 0,
+            water_jump_s: None,
+            water_jump_dir: Vector2::zero(),
+        }
+    }
+}
+
+pub(crate) fn countdown(timer_s: Option<f32>, dt: f32) -> Option<f32> {
+    timer_s
+        .map(|t| t - dt)
+        .filter(|&t| t >= 0.0)
+}
+
+fn rotation_matrix_2dof(yaw: Rad<f32>, pitch: Rad<f32>) -> Matrix3<f32> {
+    let (s0, c0) = yaw  .sin_cos();
+    let (s1, c1) = pitch.sin_cos();
+    Matrix3::new(
+            c0,     s0, 0.0,
+        -s0*c1,  c0*c1,  s1,
+         s0*s1, -c0*s1,  c1)
+}
+
+impl PlayerState {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn rotation_matrix(&self, add_yaw: Rad<f32>, add_pitch: Rad<f32>) -> Matrix3<f32> {
+        let yaw   = (self.dir.0 + add_yaw).normalize();
+        let mut pitch = self.dir.1 + add_pitch;
+        if pitch < Rad::zero      () { pitch = Rad::zero      (); }
+        if pitch > Rad::turn_div_2() { pitch = Rad::turn_div_2(); }
+        rotation_matrix_2dof(yaw, pitch)
+    }
+
+    pub fn eye_pos(&self) -> Point3<f32> {
+        self.pos + Vector3::unit_z() * self.eye_level()
+    }
+
+    pub fn view_dir(&self) -> Vector3<f32> {
+        -self.rotation_matrix(Rad::zero(), Rad::zero()).z
+    }
+
+    // as in Quake 3, ground friction is suspended for 2ms per point of knockback
+    pub fn knockback(&mut self, impulse: Vector3<f32>, knockback: f32) {
+        self.vel += impulse;
+        if self.knockback_s.is_none() {
+            self.knockback_s = Some((knockback * 0.002).clamp(0.05, 0.2));
+        }
+    }
+
+    pub fn view_matrix(&self, dt: f32, add_yaw: Rad<f32>, add_pitch: Rad<f32>) -> Matrix4<f32> {
+        let view_rot = self.rotation_matrix(add_yaw, add_pitch).transpose();
+        let offset = view_rot * -(self.pos + self.vel * dt + Vector3::unit_z() * self.eye_level()).to_vec();
+        Matrix4::from_cols(
+            view_rot.x.extend(0.0),
+            view_rot.y.extend(0.0),
+            view_rot.z.extend(0.0),
+            offset    .extend(1.0))
+    }
+
+    pub fn add_rotation(&mut self, yaw: Rad<f32>, pitch: Rad<f32>) {
+        self.dir.0 = (self.dir.0 + yaw).normalize();
+        self.dir.1 += pitch;
+        if self.dir.1 < Rad::zero      () { self.dir.1 = Rad::zero      (); }
+        if self.dir.1 > Rad::turn_div_2() { self.dir.1 = Rad::turn_div_2(); }
+    }
+
+    pub fn wish_dir(&self, key_state: KeyState, add_yaw: Rad<f32>, add_pitch: Rad<f32>) -> Vector2<f32> {
+        let rotation = self.rotation_matrix(add_yaw, add_pitch);
+        let up      = Vector3::<f32>::unit_z();
+        let right   = rotation.x;
+        let forward = up.cross(right);
+
+        let mut wish_dir = Vector3::<f32>::zero();
+        if key_state.key_w { wish_dir += forward; }
+        if key_state.key_a { wish_dir -= right; }
+        if key_state.key_s { wish_dir -= forward; }
+        if key_state.key_d { wish_dir += right; }
+        let norm = wish_dir.magnitude();
+        (if norm < 0.0001 { wish_dir } else { wish_dir / norm }).xy()
+    }
+
+    // as wish_dir, but following the view pitch and swimming up or down with jump or crouch
+    pub fn swim_dir(&self, key_state: KeyState) -> Vector3<f32> {
+        let rotation = self.rotation_matrix(Rad::zero(), Rad::zero());
+        let right   = rotation.x;
+        let forward = -rotation.z;
+
+        let mut wish_dir = Vector3::<f32>::zero();
+        if key_state.key_w { wish_dir += forward; }
+        if key_state.key_a { wish_dir -= right; }
+        if key_state.key_s { wish_dir -= forward; }
+        if key_state.key_d { wish_dir += right; }
+        if key_state.space { wish_dir += Vector3::unit_z(); }
+        if key_state.key_c { wish_dir -= Vector3::unit_z(); }
+        let norm = wish_dir.magnitude();
+        if norm < 0.0001 { wish_dir } else { wish_dir / norm }
+    }
+
+    // as in Quake 2, climbing with jump and crouch, or by moving forward while looking up or down
+    fn climb_dir(&self, key_state: KeyState) -> f32 {
+        const CLIMB_PITCH: f32 = 0.25;
+        let pitch = self.view_dir().z;
+        if key_state.space {
+            1.0
+        } else if key_state.key_c {
+            -1.0
+        } else if key_state.key_w && pitch > CLIMB_PITCH {
+            1.0
+        } else if key_state.key_w && pitch < -CLIMB_PITCH {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn forward_dir(&self) -> Vector2<f32> {
+        self.wish_dir(KeyState{key_w: true, ..KeyState::default()}, Rad::zero(), Rad::zero())
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    pub fn hull(&self) -> Hull {
+        if self.crouched { PLAYER_CROUCH_HULL } else { PLAYER_HULL }
+    }
+
+    pub fn is_double_jump_ready(&self) -> bool {
+        self.double_jump_s.is_some()
+    }
+
+    // the ground as Kinematics::sim would see it, short of looking for edges
+    pub fn ground_state(&self, keys: KeyState) -> Option<Ground> {
+        self.ground.map(|norm| Ground{
+            norm,
+            is_edge: false,
+            stance: self.stance(keys),
+            is_double_jump: self.is_double_jump_ready(),
+            landed_ticks: self.landed_ticks.unwrap_or_default(),
+            knockback: self.knockback_s.is_some(),
+        })
+    }
+
+    pub fn eye_level(&self) -> f32 {
+        if self.crouched { PLAYER_CROUCH_EYELEVEL } else { PLAYER_EYELEVEL }
+    }
+
+    pub fn stance(&self, keys: KeyState) -> Stance {
+        if self.crouched {
+            if self.slide_s.is_some() { Stance::Sliding } else { Stance::Crouching }
+        } else if keys.shift {
+            Stance::Walking
+        } else {
+            Stance::Standing
+        }
+    }
+
+    fn fits<G: Geometry + ?Sized>(geometry: &G, pos: Point3<f32>, hull: Hull) -> bool {
+        !geometry.trace(pos, pos, hull).start_solid
+    }
+
+    fn crouch<G: Geometry + ?Sized>(&mut self, is_crouching: bool, geometry: &G) {
+        let height_diff = PLAYER_HEIGHT - PLAYER_CROUCH_HEIGHT;
+        if is_crouching && !self.crouched {
+            self.crouched = true;
+            if self.ground.is_none() {
+                // tuck the legs up, so crouching mid-jump clears higher ledges
+                let up = self.pos + Vector3::unit_z() * height_diff;
+                let trace = geometry.trace(self.pos, up, PLAYER_CROUCH_HULL);
+                if !trace.all_solid {
+                    self.pos = trace.end_pos(self.pos, up);
+                }
+            }
+        } else if !is_crouching && self.crouched {
+            // stand up only if there's room, dropping the legs back down in mid-air
+            let pos = if self.ground.is_none() {
+                let down = self.pos - Vector3::unit_z() * height_diff;
+                let trace = geometry.trace(self.pos, down, PLAYER_CROUCH_HULL);
+                trace.end_pos(self.pos, down)
+            } else {
+                self.pos
+            };
+            if Self::fits(geometry, pos, PLAYER_HULL) {
+                self.pos = pos;
+                self.crouched = false;
+            } else if Self::fits(geometry, self.pos, PLAYER_HULL) {
+                self.crouched = false;
+            }
+        }
+    }
+
+    fn ground_trace<G: Geometry + ?Sized>(&self, geometry: &G) -> Option<Vector3<f32>> {
+        let end = self.pos - Vector3::unit_z() * JUMP_GROUND_DIST;
+        let trace = geometry.trace(self.pos, end, self.hull());
+        if !trace.is_hit() || trace.norm.z < MIN_WALK_NORMAL {
+            // airborne, or sliding down a slope too steep to stand on
+            None
+        } else if self.vel.z > 0.0 && self.vel.dot(trace.norm) > 10.0 {
+            // moving away from the ground, e.g. just jumped
+            None
+        } else {
+            Some(trace.norm)
+        }
+    }
+
+    fn water_level<G: Geometry + ?Sized>(&self, geometry: &G) -> u8 {
+        let point = Hull{radius: 0.0, height: 0.0};
+        let is_wet = |height: f32| {
+            geometry.touches(self.pos + Vector3::unit_z() * height, point, Contents::Water)
+        };
+        if !is_wet(1.0) {
+            0
+        } else if !is_wet(self.eye_level() / 2.0) {
+            1
+        } else if !is_wet(self.eye_level()) {
+            2
+        } else {
+            3
+        }
+    }
+
+    // as Quake 3's PM_CheckWaterJump, swimming at a ledge with room above it
+    fn is_water_jump<G: Geometry + ?Sized>(&self, keys: KeyState, geometry: &G) -> bool {
+        if self.water_level != 2 || !keys.key_w {
+            return false;
+        }
+        let point = Hull{radius: 0.0, height: 0.0};
+        let is_solid = |pos: Point3<f32>| geometry.trace(pos, pos, point).start_solid;
+        let spot = self.pos
+                 + self.forward_dir().extend(0.0) * WATER_JUMP_DIST
+                 + Vector3::unit_z() * (self.eye_level() / 2.0 + 4.0);
+        is_solid(spot) && !is_solid(spot + Vector3::unit_z() * 16.0)
+    }
+
+    fn move_state<G: Geometry + ?Sized>(&self, is_jumping: bool, geometry: &G) -> MoveState {
+        let is_ground_jump = is_jumping && self.ground.is_some();
+        if self.water_jump_s.is_some() {
+            MoveState::WaterJump
+        } else if self.water_level >= 2 {
+            MoveState::Swim
+        } else if !is_ground_jump && geometry.touches(self.pos, self.hull(), Contents::Ladder) {
+            MoveState::Ladder
+        } else if self.ground.is_some() {
+            MoveState::Ground
+        } else {
+            MoveState::Air
+        }
+    }
+
+    // checks for a drop just ahead of the player, as in GoldSrc's PM_Friction
+    fn is_edge<G: Geometry + ?Sized>(&self, geometry: &G) -> bool {
+        const EDGE_LOOKAHEAD: f32 = 16.0;
+        const EDGE_DEPTH: f32 = 34.0;
+        let speed = self.vel.xy().magnitude();
+        if self.ground.is_none() || speed < 0.0001 {
+            return false;
+        }
+        let start = self.pos + (self.vel.xy() / speed * EDGE_LOOKAHEAD).extend(0.0);
+        let end = start - Vector3::unit_z() * EDGE_DEPTH;
+        !geometry.trace(start, end, Hull{radius: 0.0, height: 0.0}).is_hit()
+    }
+
+    // returns true if the move was blocked by anything
+    fn slide_move<G: Geometry + ?Sized>(&mut self, geometry: &G, dt: f32) -> bool {
+        let hull = self.hull();
+        let primal_vel = self.vel;
+        let mut original_vel = self.vel;
+        let mut planes = [Vector3::<f32>::zero(); MAX_CLIP_PLANES];
+        let mut num_planes = 0;
+        let mut time_left = dt;
+        let mut blocked = false;
+
+        for _ in 0..MAX_BUMPS {
+            let end = self.pos + self.vel * time_left;
+            let trace = geometry.trace(self.pos, end, hull);
+
+            if trace.all_solid {
+                self.vel = Vector3::zero();
+                return true;
+            }
+
+            if trace.fraction > 0.0 {
+                self.pos = trace.end_pos(self.pos, end);
+                original_vel = self.vel;
+                num_planes = 0;
+            }
+
+            if !trace.is_hit() {
+                break;
+            }
+            blocked = true;
+
+            time_left -= time_left * trace.fraction;
+
+            if num_planes >= MAX_CLIP_PLANES {
+                self.vel = Vector3::zero();
+                break;
+            }
+            planes[num_planes] = trace.norm;
+            num_planes += 1;
+
+            // find a plane which can be slid along without entering the others
+            let clipped = (0..num_planes).find_map(|i| {
+                let vel = clip_velocity(original_vel, planes[i], OVERCLIP);
+                let is_valid = (0..num_planes).all(|j| j == i || vel.dot(planes[j]) >= 0.0);
+                if is_valid { Some(vel) } else { None }
+            });
+            if let Some(vel) = clipped {
+                self.vel = vel;
+            } else if num_planes == 2 {
+                // slide along the crease between both planes
+                let dir = planes[0].cross(planes[1]);
+                if dir.magnitude2() < 0.000_001 {
+                    self.vel = Vector3::zero();
+                    break;
+                }
+                let dir = dir.normalize();
+                self.vel = dir * dir.dot(self.vel);
+            } else {
+                self.vel = Vector3::zero();
+                break;
+            }
+
+            // avoid jittering back and forth in corners
+            if self.vel.dot(primal_vel) <= 0.0 {
+                self.vel = Vector3::zero();
+                break;
+            }
+        }
+
+        blocked
+    }
+
+    fn step_slide_move<G: Geometry + ?Sized>(&mut self, geometry: &G, dt: f32) {
+        let hull = self.hull();
+        let start_pos = self.pos;
+        let start_vel = self.vel;
+
+        if !self.slide_move(geometry, dt) {
+            return;
+        }
+
+        // don't step up while jumping, or onto slopes too steep to stand on
+        let down = start_pos - Vector3::unit_z() * STEP_SIZE;
+        let trace = geometry.trace(start_pos, down, hull);
+        let is_steep = trace.is_hit() && trace.norm.z < MIN_WALK_NORMAL;
+        if is_steep || (self.vel.z > 0.0 && !trace.is_hit()) {
+            return;
+        }
+
+        let up = start_pos + Vector3::unit_z() * STEP_SIZE;
+        let trace = geometry.trace(start_pos, up, hull);
+        if trace.all_solid {
+            return;
+        }
+        let step_pos = trace.end_pos(start_pos, up);
+        let step_height = step_pos.z - start_pos.z;
+
+        let (down_pos, down_vel) = (self.pos, self.vel);
+        self.pos = step_pos;
+        self.vel = start_vel;
+        self.slide_move(geometry, dt);
+
+        let down = self.pos - Vector3::unit_z() * step_height;
+        let trace = geometry.trace(self.pos, down, hull);
+        if !trace.all_solid {
+            self.pos = trace.end_pos(self.pos, down);
+        }
+        if trace.is_hit() {
+            self.vel = clip_velocity(self.vel, trace.norm, OVERCLIP);
+        }
+
+        // keep whichever move got further, e.g. stepping up into a low ceiling
+        let down_dist = (down_pos - start_pos).xy().magnitude2();
+        let step_dist = (self.pos - start_pos).xy().magnitude2();
+        if step_dist < down_dist {
+            self.pos = down_pos;
+            self.vel = down_vel;
+        }
+    }
+
+    pub(crate) fn is_jumping(&mut self, jump_mode: JumpMode, keys: KeyState, dt: f32) -> bool {
+        let is_jumping = match jump_mode {
+            JumpMode::Hold => keys.space,
+            JumpMode::RequireRelease => keys.space && !self.jump_held,
+            JumpMode::Buffer(window_s) => {
+                if keys.space && !self.jump_held {
+                    self.jump_held = true;
+                    self.jump_buffer_s = Some(window_s);
+                }
+                self.jump_buffer_s.is_some()
+            }
+        };
+
+        if !keys.space {
+            self.jump_held = false;
+        }
+        self.jump_buffer_s = countdown(self.jump_buffer_s, dt);
+
+        is_jumping
+    }
+
+    // jumping uses up the press, so it must be released before the next
+    pub(crate) fn use_jump(&mut self) {
+        self.jump_held = true;
+        self.jump_buffer_s = None;
+    }
+
+    // dashes along the ground or jumps off a nearby wall, as in Warsow
+    fn special_move<G: Geometry + ?Sized>(&mut self,
+        kinematics: &Kinematics,
+        wish_dir: Vector2<f32>,
+        geometry: &G)
+    {
+        if let Some(norm) = self.ground {
+            if let (Some(dash), None) = (kinematics.dash, self.dash_cooldown_s) {
+                let dir = if wish_dir.magnitude2() > 0.0 {
+                    wish_dir
+                } else {
+                    self.forward_dir()
+                };
+                self.vel = clip_velocity(self.vel, norm, OVERCLIP);
+                dash.sim(&mut self.vel, dir);
+                self.ground = None;
+                self.dash_cooldown_s = Some(dash.cooldown_s);
+            }
+        } else if let (Some(wall_jump), None) = (kinematics.wall_jump, self.wall_jump_cooldown_s) {
+            let wall = geometry.nearest_wall(self.pos, self.hull(), WALL_JUMP_DIST, MIN_WALK_NORMAL);
+            if let Some(norm) = wall {
+                wall_jump.sim(&mut self.vel, norm);
+                self.wall_jump_cooldown_s = Some(wall_jump.cooldown_s);
+            }
+        }
+    }
+
+    pub fn sim_kinematics<G: Geometry + ?Sized>(&mut self,
+        kinematics: &Kinematics,
+        dt: f32,
+        keys: KeyState,
+        geometry: &G)
+    {
+        let was_sliding = self.crouched && self.ground.is_some();
+        self.ground = self.ground_trace(geometry);
+        self.landed_ticks = if self.ground.is_some() {
+            Some(self.landed_ticks.map_or(0, |ticks| ticks + 1))
+        } else {
+            None
+        };
+
+        self.crouch(keys.key_c, geometry);
+        if self.crouched && self.ground.is_some() && !was_sliding {
+            self.slide_s = kinematics.crouch_slide.map(|slide| slide.duration_s);
+        }
+        if !self.crouched {
+            self.slide_s = None;
+        }
+
+        self.water_level = self.water_level(geometry);
+
+        // swimming and climbing follow jump as it's held, rather than jumping
+        let held_keys = keys;
+        let is_jumping = self.is_jumping(kinematics.jump_mode, keys, dt);
+        let is_double_jump = self.is_double_jump_ready();
+        if is_jumping && self.ground.is_some() {
+            self.use_jump();
+            self.double_jump_s = kinematics.double_jump.map(|double_jump| double_jump.window_s);
+            self.hop_ticks = self.landed_ticks;
+            self.perfect_hop_streak = if self.hop_ticks == Some(0) { self.perfect_hop_streak + 1 } else { 0 };
+        }
+        let keys = KeyState{space: is_jumping, ..keys};
+
+        let wish_dir = self.wish_dir(keys, Rad::zero(), Rad::zero());
+        if keys.key_e && !self.special_held && !is_jumping {
+            self.special_held = true;
+            self.special_move(kinematics, wish_dir, geometry);
+        }
+        if !keys.key_e {
+            self.special_held = false;
+        }
+
+        let forward = self.forward_dir();
+        self.move_state = self.move_state(is_jumping, geometry);
+        if self.move_state == MoveState::Swim && self.is_water_jump(held_keys, geometry) {
+            self.move_state = MoveState::WaterJump;
+            self.water_jump_s = Some(kinematics.water.jump_duration_s);
+            self.water_jump_dir = forward;
+            self.vel.z = kinematics.water.jump_upward_speed;
+        }
+
+        match self.move_state {
+            MoveState::Swim => {
+                let swim_dir = self.swim_dir(held_keys);
+                kinematics.sim_swim(&mut self.vel, dt, swim_dir, self.water_level, self.ground);
+            }
+            MoveState::WaterJump => {
+                // keep pushing into the ledge, as being blocked by it would stop the climb
+                let vel = self.water_jump_dir * kinematics.water.jump_speed;
+                self.vel.x = vel.x;
+                self.vel.y = vel.y;
+                kinematics.sim(&mut self.vel, dt, Vector2::zero(), forward, None, KeyState::default());
+            }
+            MoveState::Ladder => {
+                let climb_dir = self.climb_dir(held_keys);
+                kinematics.sim_ladder(&mut self.vel, dt, wish_dir, climb_dir);
+            }
+            MoveState::Ground | MoveState::Air => {
+                let is_edge = kinematics.edge_friction.is_some() && self.is_edge(geometry);
+                let stance = self.stance(keys);
+                let landed_ticks = self.landed_ticks.unwrap_or_default();
+                let knockback = self.knockback_s.is_some();
+                let ground = self.ground.map(|norm| Ground{norm, is_edge, stance, is_double_jump, landed_ticks, knockback});
+                kinematics.sim(&mut self.vel, dt, wish_dir, forward, ground, keys);
+                if self.water_level > 0 {
+                    kinematics.sim_wading(&mut self.vel, dt, self.water_level);
+                }
+            }
+        }
+
+        self.slide_s              = countdown(self.slide_s             , dt);
+        self.dash_cooldown_s      = countdown(self.dash_cooldown_s     , dt);
+        self.wall_jump_cooldown_s = countdown(self.wall_jump_cooldown_s, dt);
+        self.knockback_s          = countdown(self.knockback_s         , dt);
+        self.water_jump_s         = countdown(self.water_jump_s        , dt);
+        self.double_jump_s        = countdown(self.double_jump_s       , dt);
+
+        self.step_slide_move(geometry, dt);
+
+        self.ground = self.ground_trace(geometry);
+        if self.vel.z < 0.0 {
+            self.water_jump_s = None;
+        }
+
+        // jumping leaves the ground mid-tick, having taken the first half of gravity
+        let is_falling = self.ground.is_none() && match self.move_state {
+            MoveState::Air | MoveState::WaterJump => true,
+            MoveState::Ground => is_jumping,
+            MoveState::Swim | MoveState::Ladder => false,
+        };
+        kinematics.sim_finish(&mut self.vel, dt, is_falling);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.008;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.001, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn friction_above_stall_speed() {
+        // 400 - 400 * 6 * 0.008
+        let mut vel = Vector3::new(0.0, 400.0, 10.0);
+        MOVE_VQ3_LIKE.friction.sim(&mut vel, DT);
+        assert_near(vel.y, 380.8);
+        assert_near(vel.z, 10.0);
+    }
+
+    #[test]
+    fn friction_below_stall_speed() {
+        // 50 - 100 * 6 * 0.008
+        let mut vel = Vector3::new(30.0, 40.0, 0.0);
+        MOVE_VQ3_LIKE.friction.sim(&mut vel, DT);
+        assert_near(vel.x, 30.0 * 45.2 / 50.0);
+        assert_near(vel.y, 40.0 * 45.2 / 50.0);
+
+        // a drop of 4.8 stops anything slower
+        let mut vel = Vector3::new(0.0, 2.0, 0.0);
+        MOVE_VQ3_LIKE.friction.sim(&mut vel, DT);
+        assert_near(vel.y, 0.0);
+    }
+
+    #[test]
+    fn accelerate_limited_by_accel() {
+        // 3200 * 0.008 across the velocity, which doesn't count toward max_speed
+        let mut vel = Vector3::new(0.0, 300.0, 0.0);
+        MOVE_VQ3_LIKE.move_ground.sim(&mut vel, DT, Vector2::unit_x());
+        assert_near(vel.x, 25.6);
+        assert_near(vel.y, 300.0);
+    }
+
+    #[test]
+    fn accelerate_limited_by_max_speed() {
+        // only 320 - 300 is left to add along the velocity
+        let mut vel = Vector3::new(0.0, 300.0, 0.0);
+        MOVE_VQ3_LIKE.move_ground.sim(&mut vel, DT, Vector2::unit_y());
+        assert_near(vel.y, 320.0);
+
+        // never slowing anything already faster
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        MOVE_VQ3_LIKE.move_ground.sim(&mut vel, DT, Vector2::unit_y());
+        assert_near(vel.y, 400.0);
+    }
+
+    #[test]
+    fn air_strafe_gains_speed() {
+        // 320 * 0.008 at 90 degrees: sqrt(600^2 + 2.56^2)
+        let mut vel = Vector3::new(0.0, 600.0, 0.0);
+        MOVE_VQ3_LIKE.move_air.sim(&mut vel, DT, Vector2::unit_x());
+        assert_near(vel.xy().magnitude(), (600.0f32 * 600.0 + 2.56 * 2.56).sqrt());
+    }
+
+    #[test]
+    fn steps_up_onto_a_stair() {
+        use crate::collision::{Brush, DIST_EPSILON};
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0,  0.0), Matrix4::identity()),
+            Brush::from_box(Point3::new(-512.0,   64.0,   0.0), Point3::new(512.0, 512.0, 16.0), Matrix4::identity()),
+        ];
+        let keys = KeyState{key_w: true, ..KeyState::default()};
+        let mut player = PlayerState::default();
+        for _ in 0..100 {
+            player.sim_kinematics(&MOVE_VQ3_LIKE, DT, keys, &brushes[..]);
+        }
+        assert!(player.pos.y > 64.0 + PLAYER_RADIUS, "stopped at {}", player.pos.y);
+        // resting just clear of the top, as traces stop short of surfaces
+        assert!((player.pos.z - 16.0).abs() <= DIST_EPSILON, "at height {}", player.pos.z);
+        assert!(player.is_grounded());
+    }
+
+    #[test]
+    fn cpma_air_control() {
+        // forward only, 32 * 150 * 0.8 * 0.008 bends 400 toward the wish
+        let wish_dir = Vector2::new(1.0, 2.0).normalize();
+        let keys = KeyState{key_w: true, ..KeyState::default()};
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        MOVE_CPMA_LIKE.sim(&mut vel, DT, wish_dir, Vector2::unit_y(), None, keys);
+        assert_near(vel.xy().magnitude(), 400.0);
+        assert_near(vel.x, 400.0 * 13.7384 / 427.6976);
+
+        // strafing diagonally leaves it to move_air, which adds nothing here
+        let keys = KeyState{key_w: true, key_a: true, ..KeyState::default()};
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        MOVE_CPMA_LIKE.sim(&mut vel, DT, wish_dir, Vector2::unit_y(), None, keys);
+        assert_near(vel.x, 0.0);
+        assert_near(vel.y, 400.0);
+    }
+
+    #[test]
+    fn cpma_air_turning_and_stopping() {
+        // side strafing accelerates by 2100 * 0.008 instead of 320 * 0.008
+        let keys = KeyState{key_d: true, ..KeyState::default()};
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        MOVE_CPMA_LIKE.sim(&mut vel, DT, Vector2::unit_x(), Vector2::unit_y(), None, keys);
+        assert_near(vel.x, 16.8);
+
+        // wishing against the velocity brakes by 800 * 0.008
+        let keys = KeyState{key_s: true, ..KeyState::default()};
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        MOVE_CPMA_LIKE.sim(&mut vel, DT, -Vector2::unit_y(), Vector2::unit_y(), None, keys);
+        assert_near(vel.y, 393.6);
+    }
+
+    const SOURCE_TICK_RATES: [f32; 4] = [64.0, 66.0, 100.0, 128.0];
+
+    #[test]
+    fn source_air_accel_by_tick_rate() {
+        use crate::theory::Strafe;
+
+        // speed^2 grows by 30^2 a tick while 2500 * dt covers max_speed, then
+        // by 30^2 - (30 - 2500 * dt)^2, so more ticks still gain more a second
+        let expected = [57_600.0, 59_400.0, 87_500.0, 101_171.0];
+        let keys = KeyState{key_d: true, ..KeyState::default()};
+        for (&rate, &expected) in SOURCE_TICK_RATES.iter().zip(&expected) {
+            let dt = 1.0 / rate;
+            let speed = 100.0;
+            let angle = Strafe::from_movement(MOVE_SOURCE_LIKE.move_air, dt, speed).optimal_angle;
+            let wish_dir = Vector2::new(angle.0.sin(), angle.0.cos());
+            let mut vel = Vector3::new(0.0, speed, 0.0);
+            MOVE_SOURCE_LIKE.sim(&mut vel, dt, wish_dir, Vector2::unit_y(), None, keys);
+            let gain = (vel.xy().magnitude2() - speed * speed) * rate;
+            assert!((gain - expected).abs() < 1.0, "at {} tick: {} != {}", rate, gain, expected);
+        }
+    }
+
+    // strafes at the optimal angle and hops as soon as each landing allows
+    fn source_hop_speed(dt: f32, duration_s: f32) -> f32 {
+        use crate::collision::Brush;
+        use crate::theory::Strafe;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e5, -1e5, -16.0), Point3::new(1e5, 1e5, 0.0), Matrix4::identity()),
+        ];
+        let mut player = PlayerState{vel: Vector3::new(0.0, 400.0, 0.0), ..PlayerState::default()};
+        for _ in 0..(duration_s / dt) as usize {
+            let speed = player.vel.xy().magnitude();
+            let angle = Strafe::from_movement(MOVE_SOURCE_LIKE.move_air, dt, speed).optimal_angle;
+            // with D held, the wish direction is at the yaw
+            player.dir.0 = Rad(player.vel.y.atan2(player.vel.x)) - angle;
+            let keys = KeyState{key_d: true, space: player.is_grounded(), ..KeyState::default()};
+            player.sim_kinematics(&MOVE_SOURCE_LIKE, dt, keys, &brushes[..]);
+        }
+        player.vel.xy().magnitude()
+    }
+
+    #[test]
+    fn source_hop_speed_by_tick_rate() {
+        let speeds: Vec<f32> = SOURCE_TICK_RATES.iter()
+            .map(|&rate| source_hop_speed(1.0 / rate, 5.0))
+            .collect();
+        assert!(speeds[0] > 400.0, "lost speed at 64 tick: {}", speeds[0]);
+        for pair in speeds.windows(2) {
+            assert!(pair[0] < pair[1], "speeds not increasing with tick rate: {:?}", speeds);
+        }
+    }
+
+    #[test]
+    fn source_speed_caps() {
+        // running settles where friction's 4 * 250 * dt matches the accel
+        let keys = KeyState{key_w: true, ..KeyState::default()};
+        let ground = Ground{
+            norm: Vector3::unit_z(),
+            is_edge: false,
+            stance: Stance::Standing,
+            is_double_jump: false,
+            landed_ticks: 64,
+            knockback: false,
+        };
+        for &rate in &SOURCE_TICK_RATES {
+            let mut vel = Vector3::zero();
+            for _ in 0..rate as usize {
+                MOVE_SOURCE_LIKE.sim(&mut vel, 1.0 / rate, Vector2::unit_y(), Vector2::unit_y(), Some(ground), keys);
+            }
+            assert_near(vel.y, 250.0);
+        }
+
+        // sv_maxvelocity clamps each axis separately
+        let mut vel = Vector3::new(1000.0, 5000.0, -5000.0);
+        MOVE_SOURCE_LIKE.sim_finish(&mut vel, 0.015, false);
+        assert_near(vel.x, 1000.0);
+        assert_near(vel.y, 3500.0);
+        assert_near(vel.z, -3500.0);
+    }
+
+    #[test]
+    fn edge_friction() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let vel = Vector3::new(0.0, 400.0, 0.0);
+        let ground = Some(Vector3::unit_z());
+        let player = PlayerState{pos: Point3::new(0.0, 0.0, 0.0), vel, ground, ..PlayerState::default()};
+        assert!(!player.is_edge(&brushes[..]));
+        let player = PlayerState{pos: Point3::new(0.0, 500.0, 0.0), vel, ground, ..PlayerState::default()};
+        assert!(player.is_edge(&brushes[..]));
+
+        // 400 - 400 * 2 * 4 * 0.008
+        let kinematics = Kinematics{edge_friction: Some(2.0), ..MOVE_SOURCE_LIKE};
+        let ground = Ground{
+            norm: Vector3::unit_z(),
+            is_edge: true,
+            stance: Stance::Standing,
+            is_double_jump: false,
+            landed_ticks: 125,
+            knockback: false,
+        };
+        let mut vel = vel;
+        kinematics.sim(&mut vel, DT, Vector2::zero(), Vector2::unit_y(), Some(ground), KeyState::default());
+        assert_near(vel.y, 374.4);
+    }
+
+    #[test]
+    fn integrators() {
+        let brushes: [crate::collision::Brush; 0] = [];
+        let fall = |integrator: Integrator| {
+            let kinematics = Kinematics{integrator, ..MOVE_VQ3_LIKE};
+            let mut player = PlayerState{pos: Point3::new(0.0, 0.0, 1000.0), ..PlayerState::default()};
+            for _ in 0..125 {
+                player.sim_kinematics(&kinematics, DT, KeyState::default(), &brushes[..]);
+            }
+            1000.0 - player.pos.z
+        };
+        // each tick moves by its full velocity after gravity: 800 * 0.008^2 * sum(k) up to k = 125
+        assert!((fall(Integrator::Euler) - 403.2).abs() < 0.01);
+        // averaging the velocities before and after gravity is exact: 800 * 1^2 / 2
+        assert!((fall(Integrator::HalfStepGravity) - 400.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn abh_boost() {
+        // from 400, the boost of 0.5 * 320 is cut to 1.5 * 320 - 400
+        let abh = BhopCap::Abh{boost: 0.5};
+        let mut vel = Vector3::new(0.0, 400.0, 0.0);
+        abh.sim(&mut vel, Vector2::unit_y(), 1.0, 320.0);
+        assert_near(vel.y, 480.0);
+
+        // from 600, hopping forward turns the boost into 480 - 600
+        let mut vel = Vector3::new(0.0, 600.0, 0.0);
+        abh.sim(&mut vel, Vector2::unit_y(), 1.0, 320.0);
+        assert_near(vel.y, 480.0);
+
+        // while facing backward with no forward move, the boost of 480 - 600
+        // points along the velocity, and with no clamp first keeps growing
+        let mut vel = Vector3::new(0.0, 600.0, 0.0);
+        abh.sim(&mut vel, -Vector2::unit_y(), 0.0, 320.0);
+        assert_near(vel.y, 720.0);
+    }
+
+    // jumps from the floor, then presses jump for a tick this long before
+    // landing, counting the ticks taking off; presses never last past landing
+    fn takeoffs(jump_mode: JumpMode, hold: bool, press_s: f32) -> Vec<usize> {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let kinematics = Kinematics{jump_mode, ..MOVE_VQ3_LIKE};
+        // 2 * 270 / 800 in the air, then landing on the next tick
+        let press_tick = ((0.675 - press_s) / DT) as usize;
+        let mut player = PlayerState{ground: Some(Vector3::unit_z()), ..PlayerState::default()};
+        let mut takeoffs = Vec::new();
+        for i in 0..160 {
+            let space = i == 0 || hold || i == press_tick;
+            let was_grounded = player.is_grounded();
+            player.sim_kinematics(&kinematics, DT, KeyState{space, ..KeyState::default()}, &brushes[..]);
+            if was_grounded && player.vel.z > 100.0 {
+                takeoffs.push(i);
+            }
+        }
+        takeoffs
+    }
+
+    #[test]
+    fn jump_modes() {
+        // holding jump hops on every landing
+        assert_eq!(takeoffs(JumpMode::Hold, true, 0.0).len(), 2);
+        assert_eq!(takeoffs(JumpMode::RequireRelease, true, 0.0).len(), 1);
+        // a press in the air is lost by landing, unless it's buffered
+        assert_eq!(takeoffs(JumpMode::Hold, false, 0.05).len(), 1);
+        assert_eq!(takeoffs(JumpMode::RequireRelease, false, 0.05).len(), 1);
+        assert_eq!(takeoffs(JumpMode::Buffer(0.1), false, 0.05).len(), 2);
+        assert_eq!(takeoffs(JumpMode::Buffer(0.1), false, 0.2).len(), 1);
+        // buffered hops are perfect hops, the same as holding
+        assert_eq!(takeoffs(JumpMode::Buffer(0.1), false, 0.05), takeoffs(JumpMode::Hold, true, 0.0));
+    }
+
+    #[test]
+    fn walk_and_crouch_speeds() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e4, -1e4, -16.0), Point3::new(1e4, 1e4, 0.0), Matrix4::identity()),
+        ];
+        let run = |keys: KeyState| {
+            let mut player = PlayerState::default();
+            for _ in 0..125 {
+                player.sim_kinematics(&MOVE_VQ3_LIKE, DT, keys, &brushes[..]);
+            }
+            player.vel.xy().magnitude()
+        };
+        assert_near(run(KeyState{key_w: true, ..KeyState::default()}), 320.0);
+        assert_near(run(KeyState{key_w: true, shift: true, ..KeyState::default()}), 160.0);
+        assert_near(run(KeyState{key_w: true, key_c: true, ..KeyState::default()}), 80.0);
+    }
+
+    #[test]
+    fn crouched_hull() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        // a ceiling over the floor with room to crouch under it but not to stand
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0,  0.0), Matrix4::identity()),
+            Brush::from_box(Point3::new(-512.0,   64.0,  50.0), Point3::new(512.0, 512.0, 66.0), Matrix4::identity()),
+        ];
+        let crouch = KeyState{key_c: true, ..KeyState::default()};
+
+        let mut player = PlayerState::default();
+        player.sim_kinematics(&MOVE_VQ3_LIKE, DT, crouch, &brushes[..]);
+        assert!(player.crouched);
+        assert_near(player.hull().height, PLAYER_CROUCH_HEIGHT);
+        assert_near(player.eye_level(), PLAYER_CROUCH_EYELEVEL);
+        player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState::default(), &brushes[..]);
+        assert!(!player.crouched);
+
+        // under the ceiling, letting go of crouch keeps the player crouched
+        let mut player = PlayerState{pos: Point3::new(0.0, 128.0, 0.0), ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_VQ3_LIKE, DT, crouch, &brushes[..]);
+        player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState::default(), &brushes[..]);
+        assert!(player.crouched);
+
+        // crouching in mid-air tucks the legs up by the difference in height
+        let mut player = PlayerState{pos: Point3::new(0.0, 0.0, 100.0), ..PlayerState::default()};
+        let z = player.pos.z;
+        player.sim_kinematics(&MOVE_VQ3_LIKE, DT, crouch, &brushes[..]);
+        assert!(player.pos.z > z + PLAYER_HEIGHT - PLAYER_CROUCH_HEIGHT - 1.0, "at {}", player.pos.z);
+    }
+
+    #[test]
+    fn crouch_slide() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e4, -1e4, -16.0), Point3::new(1e4, 1e4, 0.0), Matrix4::identity()),
+        ];
+        let crouch = KeyState{key_c: true, ..KeyState::default()};
+        let mut player = PlayerState{vel: Vector3::new(0.0, 400.0, 0.0), ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_CPMA_LIKE, DT, crouch, &brushes[..]);
+        assert_eq!(player.stance(crouch), Stance::Sliding);
+        // 400 - 400 * 1 * 0.008, rather than 8 times as much
+        assert_near(player.vel.y, 396.8);
+
+        // after 0.5 seconds, it's back to the usual friction
+        for _ in 0..63 {
+            player.sim_kinematics(&MOVE_CPMA_LIKE, DT, crouch, &brushes[..]);
+        }
+        assert_eq!(player.stance(crouch), Stance::Crouching);
+        let speed = player.vel.y;
+        player.sim_kinematics(&MOVE_CPMA_LIKE, DT, crouch, &brushes[..]);
+        assert_near(player.vel.y, speed - speed * 8.0 * DT);
+    }
+
+    #[test]
+    fn dash() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e4, -1e4, -16.0), Point3::new(1e4, 1e4, 0.0), Matrix4::identity()),
+        ];
+        let dash = KeyState{key_w: true, key_e: true, ..KeyState::default()};
+        let mut player = PlayerState{ground: Some(Vector3::unit_z()), ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, dash, &brushes[..]);
+        assert_near(player.vel.y, 450.0);
+        assert!(player.vel.z > 170.0, "dashed up at {}", player.vel.z);
+        assert!(!player.is_grounded());
+
+        // once landed, another dash has to wait out the cooldown
+        let mut ticks = 1;
+        while !player.is_grounded() {
+            player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, KeyState::default(), &brushes[..]);
+            ticks += 1;
+        }
+        player.vel = Vector3::zero();
+        player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, dash, &brushes[..]);
+        assert!(player.is_grounded() && player.vel.y < 450.0);
+        for _ in ticks..(1.0 / DT) as usize {
+            player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, KeyState::default(), &brushes[..]);
+        }
+        player.vel = Vector3::zero();
+        player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, dash, &brushes[..]);
+        assert_near(player.vel.y, 450.0);
+    }
+
+    #[test]
+    fn wall_jump() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(24.0, -512.0, -512.0), Point3::new(64.0, 512.0, 512.0), Matrix4::identity()),
+        ];
+        let special = KeyState{key_e: true, ..KeyState::default()};
+        let vel = Vector3::new(100.0, 300.0, 0.0);
+        let mut player = PlayerState{pos: Point3::new(0.0, 0.0, 0.0), vel, ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, special, &brushes[..]);
+        // turned away from the wall, keeping speed and going up
+        assert!(player.vel.x < 0.0, "still heading into the wall at {}", player.vel.x);
+        assert!((player.vel.xy().magnitude() - vel.magnitude()).abs() < 0.01);
+        assert!(player.vel.z > 330.0, "wall jumped up at {}", player.vel.z);
+
+        // too far from any wall, nothing happens
+        let mut player = PlayerState{pos: Point3::new(-32.0, 0.0, 0.0), vel, ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_WARSOW_LIKE, DT, special, &brushes[..]);
+        assert_near(player.vel.x, 100.0);
+        assert!(player.vel.z < 0.0);
+    }
+
+    #[test]
+    fn double_jump() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let keys = KeyState{space: true, ..KeyState::default()};
+        // from the ground to landing again, returning the greatest height
+        let hop = |player: &mut PlayerState| {
+            let mut height = 0.0f32;
+            player.sim_kinematics(&MOVE_CPMA_LIKE, DT, keys, &brushes[..]);
+            while !player.is_grounded() {
+                player.sim_kinematics(&MOVE_CPMA_LIKE, DT, KeyState::default(), &brushes[..]);
+                height = height.max(player.pos.z);
+            }
+            height
+        };
+
+        // about 270^2 / (2 * 800), and a hop from that landing is no higher,
+        // as the window since the first takeoff has closed
+        let mut player = PlayerState{ground: Some(Vector3::unit_z()), ..PlayerState::default()};
+        let first = hop(&mut player);
+        assert!(first > 44.0 && first < 48.0, "jumped {}", first);
+        let second = hop(&mut player);
+        assert!((second - first).abs() < 1.0, "hopped {} after {}", second, first);
+
+        // landing 0.2 seconds after taking off, as onto a ledge, then
+        // jumping again reaches about 370^2 / (2 * 800)
+        let mut player = PlayerState{ground: Some(Vector3::unit_z()), ..PlayerState::default()};
+        player.sim_kinematics(&MOVE_CPMA_LIKE, DT, keys, &brushes[..]);
+        for _ in 0..25 {
+            player.sim_kinematics(&MOVE_CPMA_LIKE, DT, KeyState::default(), &brushes[..]);
+        }
+        assert!(player.is_double_jump_ready());
+        player.pos.z = 0.0;
+        player.vel.z = 0.0;
+        player.ground = Some(Vector3::unit_z());
+        let boosted = hop(&mut player);
+        assert!(boosted > 84.0 && boosted < 88.0, "double jumped {}", boosted);
+    }
+
+    fn volume(contents: crate::collision::Contents) -> [crate::collision::Volume; 1] {
+        use crate::collision::{Brush, Volume};
+        use cgmath::SquareMatrix;
+
+        let brush = Brush::from_box(Point3::new(-1e4, -1e4, -1e4), Point3::new(1e4, 1e4, 1e4), Matrix4::identity());
+        [Volume::new(brush, contents)]
+    }
+
+    #[test]
+    fn swimming() {
+        let water = volume(crate::collision::Contents::Water);
+
+        // without swimming, friction and the sink speed settle at 60 down
+        let mut player = PlayerState::default();
+        for _ in 0..250 {
+            player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState::default(), &water[..]);
+        }
+        assert_eq!(player.water_level, 3);
+        assert_eq!(player.move_state, MoveState::Swim);
+        assert_near(player.vel.z, -60.0);
+
+        // swimming along the view is capped at half of running speed, and
+        // unaffected by gravity
+        let mut player = PlayerState::default();
+        for _ in 0..250 {
+            player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState{key_w: true, ..KeyState::default()}, &water[..]);
+        }
+        assert_near(player.vel.y, 160.0);
+        assert_near(player.vel.z, 0.0);
+    }
+
+    #[test]
+    fn climbing() {
+        let ladder = volume(crate::collision::Contents::Ladder);
+
+        let climb = |keys: KeyState| {
+            let mut player = PlayerState::default();
+            for _ in 0..125 {
+                player.sim_kinematics(&MOVE_VQ3_LIKE, DT, keys, &ladder[..]);
+            }
+            assert_eq!(player.move_state, MoveState::Ladder);
+            player.vel
+        };
+        // without gravity, standing still on the ladder stays put
+        assert_near(climb(KeyState::default()).z, 0.0);
+        assert_near(climb(KeyState{space: true, ..KeyState::default()}).z, 200.0);
+        assert_near(climb(KeyState{key_c: true, ..KeyState::default()}).z, -200.0);
+        // sidestepping barely outpaces friction
+        let speed = climb(KeyState{key_d: true, ..KeyState::default()}).xy().magnitude();
+        assert!(speed > 0.0 && speed < 25.0, "sidestepped at {}", speed);
+    }
+
+    #[test]
+    fn landing_friction() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e4, -1e4, -16.0), Point3::new(1e4, 1e4, 0.0), Matrix4::identity()),
+        ];
+        // friction starts on the third tick on the ground, taking 400 * 6 * 0.008
+        let kinematics = Kinematics{landing_friction: LandingFriction::FrictionFree(2), ..MOVE_VQ3_LIKE};
+        let mut player = PlayerState{vel: Vector3::new(0.0, 400.0, 0.0), ..PlayerState::default()};
+        for &speed in &[400.0, 400.0, 380.8] {
+            player.sim_kinematics(&kinematics, DT, KeyState::default(), &brushes[..]);
+            assert_near(player.vel.y, speed);
+        }
+
+        // immediate friction applies even when jumping on landing
+        let kinematics = Kinematics{landing_friction: LandingFriction::Immediate, ..MOVE_VQ3_LIKE};
+        let mut player = PlayerState{vel: Vector3::new(0.0, 400.0, 0.0), ..PlayerState::default()};
+        player.sim_kinematics(&kinematics, DT, KeyState{space: true, ..KeyState::default()}, &brushes[..]);
+        assert!(!player.is_grounded());
+        assert_near(player.vel.y, 380.8);
+    }
+
+    #[test]
+    fn perfect_hop_streak() {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-1e4, -1e4, -16.0), Point3::new(1e4, 1e4, 0.0), Matrix4::identity()),
+        ];
+        // lands, waits on the ground this many ticks, then jumps
+        let hop = |player: &mut PlayerState, wait_ticks: usize| {
+            while !player.is_grounded() {
+                player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState::default(), &brushes[..]);
+            }
+            for _ in 0..wait_ticks {
+                player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState::default(), &brushes[..]);
+            }
+            player.sim_kinematics(&MOVE_VQ3_LIKE, DT, KeyState{space: true, ..KeyState::default()}, &brushes[..]);
+        };
+
+        let mut player = PlayerState{ground: Some(Vector3::unit_z()), ..PlayerState::default()};
+        for streak in 1..=3 {
+            hop(&mut player, 0);
+            assert_eq!(player.hop_ticks, Some(0));
+            assert_eq!(player.perfect_hop_streak, streak);
+        }
+        hop(&mut player, 2);
+        assert_eq!(player.hop_ticks, Some(2));
+        assert_eq!(player.perfect_hop_streak, 0);
+        hop(&mut player, 0);
+        assert_eq!(player.perfect_hop_streak, 1);
+    }
+
+    fn jump_height(kinematics: &Kinematics, dt: f32) -> f32 {
+        use crate::collision::Brush;
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let keys = KeyState{space: true, ..KeyState::default()};
+        let mut player = PlayerState::default();
+        let mut height = 0.0f32;
+        for _ in 0..(0.6 / dt) as usize {
+            player.sim_kinematics(kinematics, dt, keys, &brushes[..]);
+            height = height.max(player.pos.z);
+        }
+        height
+    }
+
+    #[test]
+    fn q3_jump_height() {
+        let kinematics = Kinematics{q3_fidelity: true, ..MOVE_VQ3_LIKE};
+        // at 125 fps, gravity takes 6.4 a tick, but 270 - 6.4 = 263.6 rounds to
+        // the nearest whole unit, 264, so each tick loses 6 instead of 6.4, for
+        // 0.008 * sum(266.8 - 6k) up to k = 44, enough to clear Quake 3's well
+        // known 48 unit ledges
+        assert_near(jump_height(&kinematics, 0.008), 48.528);
+        // at 333 fps, each tick loses 2 instead of 2.4, for 0.003 * sum(268.8 - 2k) up to k = 134
+        assert_near(jump_height(&kinematics, 0.003), 54.594);
+        // without snapping, each tick's move is exact, so 270^2 / (2 * 800)
+        let kinematics = Kinematics{integrator: Integrator::HalfStepGravity, ..MOVE_VQ3_LIKE};
+        assert!((jump_height(&kinematics, 0.008) - 45.5625).abs() < 0.1);
+    }
+
+    #[test]
+    fn q3_overbounce() {
+        use crate::collision::{Brush, DIST_EPSILON};
+        use cgmath::SquareMatrix;
+
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let kinematics = Kinematics{q3_fidelity: true, ..MOVE_VQ3_LIKE};
+        let drop = |height: f32| {
+            let mut player = PlayerState{pos: Point3::new(0.0, 0.0, height), ..PlayerState::default()};
+            while !player.is_grounded() {
+                player.sim_kinematics(&kinematics, 0.008, KeyState::default(), &brushes[..]);
+            }
+            let landing = (player.pos.z, player.vel.z);
+            player.sim_kinematics(&kinematics, 0.008, KeyState::default(), &brushes[..]);
+            (landing, player.vel.z)
+        };
+
+        // falling from 51 at 125 fps, the last tick stops 0.142 short of the
+        // floor, close enough to be grounded while still falling at 273; the
+        // ground clip leaves only 0.273 upward, which rescaling back to the
+        // full speed turns into a bounce straight back up
+        let ((z, vel_z), bounce) = drop(51.0);
+        assert!(z > DIST_EPSILON && z < JUMP_GROUND_DIST, "landed at {}", z);
+        assert_near(vel_z, -273.0);
+        assert_near(bounce, 273.0);
+
+        // from 52, the fall meets the floor and stops
+        let (_, bounce) = drop(52.0);
+        assert!(bounce <= 1.0, "bounced at {}", bounce);
+    }
+}
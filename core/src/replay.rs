@@ -0,0 +1,108 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+
+use cgmath::Rad;
+
+use crate::collision::Geometry;
+use crate::input::KeyState;
+use crate::player::{Kinematics, PlayerState};
+
+#[derive(Copy, Clone)]
+pub struct TickInput {
+    pub dt: f32,
+    pub keys: KeyState,
+    pub dir: (Rad<f32>, Rad<f32>),
+}
+
+// longest recording kept for replaying when kinematics change, so rewinding
+// stays quick however long a run goes on
+pub const MAX_RECORDING_S: f32 = 30.0;
+
+// a second player, driven by the inputs recorded from the first, for
+// comparing how different kinematics play out; weapons aren't replayed
+pub struct Ghost {
+    pub kinematics: Kinematics,
+    pub player_state: PlayerState,
+    start: PlayerState,
+    inputs: Vec<TickInput>,
+    recorded_s: f32,
+}
+
+impl Ghost {
+    pub fn new(kinematics: Kinematics, start: PlayerState) -> Self {
+        Self{
+            kinematics,
+            player_state: start.clone(),
+            start,
+            inputs: Vec::new(),
+            recorded_s: 0.0,
+        }
+    }
+
+    pub fn restart(&mut self, start: PlayerState) {
+        self.player_state = start.clone();
+        self.start = start;
+        self.inputs.clear();
+        self.recorded_s = 0.0;
+    }
+
+    // once MAX_RECORDING_S is recorded, recording starts over from here
+    pub fn sim<G: Geometry + ?Sized>(&mut self, input: TickInput, geometry: &G) {
+        if self.recorded_s + input.dt > MAX_RECORDING_S {
+            self.restart(self.player_state.clone());
+        }
+        self.inputs.push(input);
+        self.recorded_s += input.dt;
+        self.player_state.dir = input.dir;
+        self.player_state.sim_kinematics(&self.kinematics, input.dt, input.keys, geometry);
+    }
+
+    // rewinds to the start of the recording, at most MAX_RECORDING_S ago; the
+    // returned inputs should be passed back to sim
+    pub fn set_kinematics(&mut self, kinematics: Kinematics) -> Vec<TickInput> {
+        self.kinematics = kinematics;
+        self.player_state = self.start.clone();
+        self.recorded_s = 0.0;
+        std::mem::take(&mut self.inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::{Matrix4, Point3, SquareMatrix};
+
+    use crate::collision::Brush;
+    use crate::player::MOVE_VQ3_LIKE;
+
+    #[test]
+    fn recording_is_capped() {
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        let input = TickInput{dt: 0.01, keys: KeyState::default(), dir: (Rad(0.0), Rad(0.0))};
+        let mut ghost = Ghost::new(MOVE_VQ3_LIKE, PlayerState::default());
+        for _ in 0..4000 {
+            ghost.sim(input, &brushes[..]);
+        }
+        let inputs = ghost.set_kinematics(MOVE_VQ3_LIKE);
+        // 40 seconds in, recording started over at about 30
+        assert!((999..=1001).contains(&inputs.len()), "{} inputs", inputs.len());
+    }
+}
@@ -0,0 +1,241 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use cgmath::prelude::*;
+
+use cgmath::{Rad, Vector2, Vector3};
+
+use crate::input::KeyState;
+use crate::player::{Ground, Kinematics, Movement, PlayerState, Stance};
+
+// the best strafe angle is searched for over half a turn, then refined around
+// the best so far, as its window narrows at higher speeds
+const SEARCH_STEPS: usize = 36;
+const SEARCH_REFINEMENTS: usize = 5;
+const SEARCH_REFINE_STEPS: usize = 5;
+const SEARCH_BISECTIONS: usize = 24;
+
+// speed after a tick of accelerating at an angle from the velocity, as in
+// Quake's PM_Accelerate
+pub fn speed_after(movement: Movement, dt: f32, speed: f32, angle: Rad<f32>) -> f32 {
+    let proj = speed * angle.cos();
+    let add_speed = (movement.max_speed - proj).max(0.0).min(movement.accel * dt);
+    (speed * speed + 2.0 * proj * add_speed + add_speed * add_speed).max(0.0).sqrt()
+}
+
+// the angle from the velocity, up to half a turn, leaving the most speed,
+// and that speed
+pub fn best_angle<F: Fn(Rad<f32>) -> f32>(speed_at: F) -> (Rad<f32>, f32) {
+    let best_of = |angles: &mut dyn Iterator<Item = f32>| angles
+        .map(|angle| (angle, speed_at(Rad(angle))))
+        .fold((0.0, f32::MIN), |best, next| if next.1 > best.1 { next } else { best });
+
+    let mut step = std::f32::consts::PI / SEARCH_STEPS as f32;
+    let mut best = best_of(&mut (0..=SEARCH_STEPS).map(|i| i as f32 * step));
+    for _ in 0..SEARCH_REFINEMENTS {
+        let center = best.0;
+        step /= SEARCH_REFINE_STEPS as f32;
+        let n = SEARCH_REFINE_STEPS as isize;
+        best = best_of(&mut (-n..=n).map(|i| center + i as f32 * step));
+    }
+    (Rad(best.0), best.1)
+}
+
+// the angle between start and end where is_past turns from false to true
+pub fn bisect<F: Fn(Rad<f32>) -> bool>(is_past: F, start: Rad<f32>, end: Rad<f32>) -> Rad<f32> {
+    let mut range = (start, end);
+    for _ in 0..SEARCH_BISECTIONS {
+        let middle = (range.0 + range.1) / 2.0;
+        if is_past(middle) {
+            range.1 = middle;
+        } else {
+            range.0 = middle;
+        }
+    }
+    range.1
+}
+
+// the window of strafe angles, as shown on the HUD
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Strafe {
+    // strafing is the only way to go any faster
+    pub max_speed: f32,
+    // any closer to the velocity and acceleration is cut short by max_speed
+    pub min_angle: Rad<f32>,
+    // full acceleration, as close to the velocity as possible
+    pub optimal_angle: Rad<f32>,
+    // any further from the velocity and speed is lost
+    pub max_angle: Rad<f32>,
+    // speed gained per tick at the optimal angle
+    pub gain: f32,
+}
+
+impl Strafe {
+    // stance is None while airborne, as with Kinematics::effective_movement
+    pub fn new(kinematics: &Kinematics,
+        stance: Option<Stance>,
+        is_turning: bool,
+        dt: f32,
+        speed: f32) -> Self
+    {
+        let mut strafe = Self::from_movement(kinematics.effective_movement(stance, is_turning), dt, speed);
+        // past 90 degrees, CPMA-style air stopping takes over
+        if stance.is_none() && !is_turning {
+            if let Some(move_air_stopping) = kinematics.move_air_stopping {
+                strafe.max_angle = Self::from_movement(move_air_stopping, dt, speed).max_angle;
+            }
+        }
+        strafe
+    }
+
+    // as new, but searching a simulated tick for the optimal and max angles,
+    // which also covers air control, bunnyhop caps and the like
+    pub fn simulate(kinematics: &Kinematics,
+        vel: Vector3<f32>,
+        ground: Option<Ground>,
+        keys: KeyState,
+        dt: f32) -> Self
+    {
+        let speed = vel.xy().magnitude();
+        let stance = ground
+            .filter(|ground| !keys.space && !ground.knockback)
+            .map(|ground| ground.stance);
+        let strafe = Self::new(kinematics, stance, keys.is_side_strafe(), dt, speed);
+        let key_dir = PlayerState::default().wish_dir(keys, Rad::zero(), Rad::zero());
+        if speed < 0.0001 || key_dir.magnitude2() < 0.5 {
+            return strafe;
+        }
+
+        // the wish direction is put counterclockwise of the velocity, with
+        // the view turned to match
+        let heading = Vector2::unit_y().angle(vel.xy());
+        let key_angle = Vector2::unit_y().angle(key_dir);
+        let speed_at = |angle: Rad<f32>| {
+            let (sin, cos) = (heading + angle).sin_cos();
+            let wish_dir = Vector2::new(-sin, cos);
+            let (sin, cos) = (heading + angle - key_angle).sin_cos();
+            let forward = Vector2::new(-sin, cos);
+            let mut vel = vel;
+            kinematics.sim(&mut vel, dt, wish_dir, forward, ground, keys);
+            vel.xy().magnitude()
+        };
+        let (optimal_angle, best_speed) = best_angle(speed_at);
+        Self{
+            optimal_angle,
+            max_angle: bisect(|angle| speed_at(angle) < speed, optimal_angle, Rad::turn_div_2()),
+            gain: best_speed - speed,
+            ..strafe
+        }
+    }
+
+    pub fn from_movement(movement: Movement, dt: f32, speed: f32) -> Self {
+        let accel = movement.accel * dt;
+        // from the projection of the velocity onto the wish direction
+        let angle = |proj: f32| Rad((proj / speed.max(0.0001)).clamp(-1.0, 1.0).acos());
+        // speed grows as the wish direction nears the velocity until the add is
+        // cut short by max_speed, after which speed^2 grows by max_speed^2 - proj^2;
+        // when a tick's accel is more than max_speed, that's best at 90 degrees
+        let optimal_angle = angle((movement.max_speed - accel).max(0.0));
+        let max_angle = angle(-(accel / 2.0).min(movement.max_speed));
+        Self{
+            max_speed: movement.max_speed,
+            min_angle: angle(movement.max_speed),
+            optimal_angle,
+            max_angle,
+            gain: speed_after(movement, dt, speed, optimal_angle) - speed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::player::{MOVE_QW_LIKE, MOVE_SOURCE_LIKE, MOVE_VQ3_LIKE};
+
+    // the best speed_after over a fine sweep of angles
+    fn brute_force(movement: Movement, dt: f32, speed: f32) -> f32 {
+        const STEPS: u32 = 100_000;
+        (0..=STEPS)
+            .map(|i| speed_after(movement, dt, speed, Rad(std::f32::consts::PI * i as f32 / STEPS as f32)))
+            .fold(0.0, f32::max)
+    }
+
+    fn assert_optimal(movement: Movement, dt: f32, speed: f32) {
+        let strafe = Strafe::from_movement(movement, dt, speed);
+        let best = brute_force(movement, dt, speed);
+        assert!((speed + strafe.gain - best).abs() < 0.001,
+            "at {}: gain {} but {} is possible", speed, strafe.gain, best - speed);
+    }
+
+    #[test]
+    fn optimal_angle_ground() {
+        for &speed in &[100.0, 320.0, 400.0, 800.0] {
+            assert_optimal(MOVE_VQ3_LIKE.move_ground, 0.008, speed);
+        }
+    }
+
+    #[test]
+    fn optimal_angle_air() {
+        for &speed in &[320.0, 400.0, 800.0, 1500.0] {
+            assert_optimal(MOVE_VQ3_LIKE.move_air, 0.008, speed);
+        }
+    }
+
+    #[test]
+    fn optimal_angle_accel_over_max_speed() {
+        // 3200 * 0.01 and 2500 * 1/66 are both more than 30
+        for &speed in &[320.0, 400.0, 800.0, 1500.0] {
+            assert_optimal(MOVE_QW_LIKE.move_air, 0.01, speed);
+            assert_optimal(MOVE_SOURCE_LIKE.move_air, 1.0 / 66.0, speed);
+            assert_optimal(MOVE_SOURCE_LIKE.move_air, 1.0 / 64.0, speed);
+        }
+        let strafe = Strafe::from_movement(MOVE_QW_LIKE.move_air, 0.01, 400.0);
+        assert!((strafe.optimal_angle.0 - std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+    }
+
+    #[test]
+    fn simulate_matches_analytic() {
+        let keys = KeyState{key_d: true, ..KeyState::default()};
+        for &speed in &[320.0, 400.0, 800.0, 1500.0] {
+            let vel = Vector3::new(0.0, speed, 0.0);
+            let strafe = Strafe::simulate(&MOVE_VQ3_LIKE, vel, None, keys, 0.008);
+            let analytic = Strafe::from_movement(MOVE_VQ3_LIKE.move_air, 0.008, speed);
+            assert!((strafe.gain - analytic.gain).abs() < 0.001, "at {}: {} != {}", speed, strafe.gain, analytic.gain);
+            assert!((strafe.optimal_angle.0 - analytic.optimal_angle.0).abs() < 0.001);
+            assert!((strafe.max_angle.0 - analytic.max_angle.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn simulate_ground_friction() {
+        // friction takes 400 * 4 * 0.008 before accelerating
+        let keys = KeyState{key_d: true, ..KeyState::default()};
+        let vel = Vector3::new(0.0, 400.0, 0.0);
+        let ground = Ground{
+            norm: Vector3::unit_z(),
+            is_edge: false,
+            stance: Stance::Standing,
+            is_double_jump: false,
+            landed_ticks: 5,
+            knockback: false,
+        };
+        let strafe = Strafe::simulate(&MOVE_SOURCE_LIKE, vel, Some(ground), keys, 0.008);
+        let analytic = Strafe::from_movement(MOVE_SOURCE_LIKE.move_ground, 0.008, 387.2);
+        assert!((strafe.gain - (analytic.gain - 12.8)).abs() < 0.001, "{} != {}", strafe.gain, analytic.gain - 12.8);
+    }
+}
@@ -0,0 +1,208 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use cgmath::prelude::*;
+
+use cgmath::{Point3, Vector3};
+
+use crate::collision::{Geometry, Hull};
+use crate::input::KeyState;
+use crate::player::{countdown, PlayerState};
+
+// g_knockback over the mass of a player, as in Quake 3
+const KNOCKBACK_SCALE: f32 = 1000.0 / 200.0;
+const MAX_KNOCKBACK: f32 = 200.0;
+// explosions push toward a point this far above the player's center, so
+// blasts at the player's feet lift them
+const KNOCKBACK_LIFT: f32 = 24.0;
+const MUZZLE_OFFSET: f32 = 14.0;
+const POINT_HULL: Hull = Hull{radius: 0.0, height: 0.0};
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Weapon {
+    pub speed: f32,
+    pub splash_damage: f32,
+    pub splash_radius: f32,
+    pub refire_s: f32,
+    pub lifetime_s: f32,
+}
+
+pub const WEAPON_ROCKET: Weapon = Weapon{
+    speed: 900.0,
+    splash_damage: 100.0,
+    splash_radius: 120.0,
+    refire_s: 0.8,
+    lifetime_s: 15.0,
+};
+
+pub const WEAPON_PLASMA: Weapon = Weapon{
+    speed: 2000.0,
+    splash_damage: 15.0,
+    splash_radius: 20.0,
+    refire_s: 0.1,
+    lifetime_s: 10.0,
+};
+
+#[derive(Copy, Clone)]
+pub struct Projectile {
+    pub pos: Point3<f32>,
+    pub vel: Vector3<f32>,
+    pub weapon: Weapon,
+    pub age_s: f32,
+}
+
+impl Projectile {
+    // distance from the explosion to the nearest point of the player's hull
+    fn splash_dist(pos: Point3<f32>, player: &PlayerState) -> f32 {
+        let hull = player.hull();
+        let offset = pos - player.pos;
+        let dxy = (offset.xy().magnitude() - hull.radius).max(0.0);
+        let dz = if offset.z < 0.0 {
+            -offset.z
+        } else {
+            (offset.z - hull.height).max(0.0)
+        };
+        dxy.hypot(dz)
+    }
+
+    fn explode<G: Geometry + ?Sized>(&self, player: &mut PlayerState, geometry: &G) {
+        let dist = Self::splash_dist(self.pos, player);
+        if dist >= self.weapon.splash_radius {
+            return;
+        }
+
+        let center = player.pos + Vector3::unit_z() * (player.hull().height / 2.0);
+        if geometry.trace(self.pos, center, POINT_HULL).is_hit() {
+            return;
+        }
+
+        let points = self.weapon.splash_damage * (1.0 - dist / self.weapon.splash_radius);
+        let knockback = points.min(MAX_KNOCKBACK);
+        let dir = center - self.pos + Vector3::unit_z() * KNOCKBACK_LIFT;
+        if dir.magnitude2() > 0.0 {
+            player.knockback(dir.normalize() * knockback * KNOCKBACK_SCALE, knockback);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Arsenal {
+    pub projectiles: Vec<Projectile>,
+    refire_s: Option<f32>,
+}
+
+impl Arsenal {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn sim<G: Geometry + ?Sized>(&mut self,
+        weapon: Option<Weapon>,
+        player: &mut PlayerState,
+        dt: f32,
+        keys: KeyState,
+        geometry: &G)
+    {
+        self.refire_s = countdown(self.refire_s, dt);
+
+        if keys.mouse_left && self.refire_s.is_none() {
+            if let Some(weapon) = weapon {
+                let dir = player.view_dir();
+                self.projectiles.push(Projectile{
+                    pos: player.eye_pos() + dir * MUZZLE_OFFSET,
+                    vel: dir * weapon.speed,
+                    weapon,
+                    age_s: 0.0,
+                });
+                self.refire_s = Some(weapon.refire_s);
+            }
+        }
+
+        // projectiles pass through their owner, as in Quake 3
+        self.projectiles.retain_mut(|projectile| {
+            let end = projectile.pos + projectile.vel * dt;
+            let trace = geometry.trace(projectile.pos, end, POINT_HULL);
+            projectile.pos = trace.end_pos(projectile.pos, end);
+            projectile.age_s += dt;
+            if trace.is_hit() || projectile.age_s > projectile.weapon.lifetime_s {
+                projectile.explode(player, geometry);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cgmath::{Matrix4, Rad, SquareMatrix};
+
+    use crate::collision::Brush;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.001, "{} != {}", a, b);
+    }
+
+    fn explode(pos: Point3<f32>, brushes: &[Brush]) -> PlayerState {
+        let mut player = PlayerState::default();
+        let rocket = Projectile{pos, vel: Vector3::zero(), weapon: WEAPON_ROCKET, age_s: 0.0};
+        rocket.explode(&mut player, brushes);
+        player
+    }
+
+    #[test]
+    fn splash_knockback() {
+        // at the feet, full damage pushes straight up by 100 * 1000 / 200
+        let player = explode(Point3::new(0.0, 0.0, 0.0), &[]);
+        assert_near(player.vel.x, 0.0);
+        assert_near(player.vel.z, 500.0);
+        assert!(player.knockback_s.is_some());
+
+        // 60 units from the side of the hull, half damage pushes away and up
+        let player = explode(Point3::new(-76.0, 0.0, 36.0), &[]);
+        assert_near(player.vel.magnitude(), 250.0);
+        assert_near(player.vel.x / player.vel.z, 76.0 / 24.0);
+
+        // nothing beyond the splash radius, or through a wall
+        let player = explode(Point3::new(-136.0, 0.0, 36.0), &[]);
+        assert_near(player.vel.magnitude(), 0.0);
+        let wall = Brush::from_box(Point3::new(-48.0, -512.0, -512.0), Point3::new(-32.0, 512.0, 512.0), Matrix4::identity());
+        let player = explode(Point3::new(-76.0, 0.0, 36.0), &[wall]);
+        assert_near(player.vel.magnitude(), 0.0);
+    }
+
+    #[test]
+    fn rocket_jump() {
+        let brushes = [
+            Brush::from_box(Point3::new(-512.0, -512.0, -16.0), Point3::new(512.0, 512.0, 0.0), Matrix4::identity()),
+        ];
+        // with no pitch, the view is straight down
+        let mut player = PlayerState{dir: (Rad(0.0), Rad(0.0)), ..PlayerState::default()};
+        let mut arsenal = Arsenal::default();
+        let fire = KeyState{mouse_left: true, ..KeyState::default()};
+        arsenal.sim(Some(WEAPON_ROCKET), &mut player, 0.008, fire, &brushes[..]);
+        assert_eq!(arsenal.projectiles.len(), 1);
+        while !arsenal.projectiles.is_empty() {
+            arsenal.sim(Some(WEAPON_ROCKET), &mut player, 0.008, KeyState::default(), &brushes[..]);
+        }
+        // the rocket hits the floor at the player's feet
+        assert_near(player.vel.z, 500.0);
+    }
+}
@@ -15,7 +15,6 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::collision::{Box2D, mat_drop_z};
 use crate::gl_context::GlContext;
 use crate::gfx::{
     build_vbo,
@@ -24,25 +23,27 @@ use crate::gfx::{
     ConstantValue,
     draw_pass,
     gen_box,
+    gen_ramp,
     InstanceData,
     Mesh,
     Program,
     VertexAttrib,
     VERTEX_ATTRIB_DEFAULT,
 };
-use crate::player::{PlayerState, PLAYER_RADIUS};
 
 use cgmath::prelude::*;
 use rand::prelude::*;
 
-use cgmath::{Matrix4, Point3, Rad, Vector2, Vector3};
+use cgmath::{Deg, Matrix4, Point3, Rad, Vector2, Vector3};
+use strafe_core::collision::{Brush, Contents, Geometry, Hull, Plane3D, Trace, Volume};
+use strafe_core::player::{PlayerState, PLAYER_RADIUS, STEP_SIZE};
 use web_sys::WebGlRenderingContext;
 
-pub trait Map {
+pub trait Map: Geometry {
     fn atmosphere_color(&self) -> Color;
     fn interact(&mut self, player: &mut PlayerState);
     fn draw(&self,
-        gl: &GlContext,
+        gl: &dyn GlContext,
         program: &Program,
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>);
@@ -51,6 +52,72 @@ pub trait Map {
 const WALL_THICKNESS: f32 = 8.0;
 const BOX_WIDTH: f32 = 128.0;
 
+fn floor_brush() -> Brush {
+    Brush::new(vec![Plane3D::new(Vector3::unit_z(), 0.0)])
+}
+
+fn gen_ramp_section(gl: &dyn GlContext,
+    width: f32,
+    length: f32,
+    angle: Rad<f32>,
+    transform: Matrix4<f32>) -> ((Mesh, Matrix4<f32>), Brush)
+{
+    let height = length * angle.tan();
+    let min = Point3::new(-width / 2.0, -length / 2.0, 0.0);
+    let max = Point3::new( width / 2.0,  length / 2.0, height);
+    let mesh = gen_ramp(gl, min, max, 64.0).unwrap();
+    ((mesh, transform), Brush::from_ramp(min, max, transform))
+}
+
+const POOL_SIZE: f32 = 512.0;
+const POOL_DEPTH: f32 = 80.0;
+const POOL_WALL_HEIGHT: f32 = 96.0;
+// thick enough that swimming into the wall finds it for a water jump
+const POOL_WALL_THICKNESS: f32 = 16.0;
+const LADDER_WIDTH: f32 = 64.0;
+const LADDER_DEPTH: f32 = 4.0;
+
+// walled pool of water, with a ladder up the outside of one wall
+#[allow(clippy::type_complexity)]
+fn gen_pool_section(gl: &dyn GlContext,
+    transform: Matrix4<f32>) -> (Vec<(Mesh, Matrix4<f32>)>, Vec<Brush>, Vec<Volume>)
+{
+    let inner = POOL_SIZE / 2.0;
+    let outer = inner + POOL_WALL_THICKNESS;
+    let walls = [
+        (Point3::new(-outer, -outer, 0.0), Point3::new( outer, -inner, POOL_WALL_HEIGHT)),
+        (Point3::new(-outer,  inner, 0.0), Point3::new( outer,  outer, POOL_WALL_HEIGHT)),
+        (Point3::new(-outer, -inner, 0.0), Point3::new(-inner,  inner, POOL_WALL_HEIGHT)),
+        (Point3::new( inner, -inner, 0.0), Point3::new( outer,  inner, POOL_WALL_HEIGHT)),
+    ];
+    let water = (
+        Point3::new(-inner, -inner, 0.0),
+        Point3::new( inner,  inner, POOL_DEPTH));
+    // reaches above the wall, so climbing carries players over the top
+    let ladder = (
+        Point3::new(-LADDER_WIDTH / 2.0, -outer - LADDER_DEPTH, 0.0),
+        Point3::new( LADDER_WIDTH / 2.0, -outer, POOL_WALL_HEIGHT + 8.0));
+
+    let mut meshes = Vec::new();
+    meshes.extend(walls.iter().map(|&(min, max)| (gen_box(gl, min, max, 64.0).unwrap(), transform)));
+    meshes.push((gen_box(gl,
+        Point3::new(water.0.x, water.0.y, POOL_DEPTH - 1.0),
+        water.1,
+        128.0).unwrap(), transform));
+    meshes.push((gen_box(gl,
+        Point3::new(ladder.0.x, -outer - 1.0, 0.0),
+        Point3::new(ladder.1.x, -outer, POOL_WALL_HEIGHT),
+        8.0).unwrap(), transform));
+    let brushes = walls.iter()
+        .map(|&(min, max)| Brush::from_box(min, max, transform))
+        .collect();
+    let volumes = vec![
+        Volume::new(Brush::from_box(water .0, water .1, transform), Contents::Water ),
+        Volume::new(Brush::from_box(ladder.0, ladder.1, transform), Contents::Ladder),
+    ];
+    (meshes, brushes, volumes)
+}
+
 enum InstanceTransforms {
     Instanced(InstanceData),
     Fallback(Vec<Matrix4<f32>>),
@@ -63,10 +130,12 @@ pub struct Runway {
     wall_mesh: Mesh,
     scenery_mesh: Mesh,
     scenery_transforms: InstanceTransforms,
+    ramps: Vec<(Mesh, Matrix4<f32>)>,
+    collision: Vec<Brush>,
 }
 
 impl Runway {
-    pub fn new(gl: &GlContext) -> Self {
+    pub fn new(gl: &dyn GlContext) -> Self {
         const LENGTH: f32 = 16384.0;
         const WIDTH : f32 = 2048.0;
         let scenery_transforms = {
@@ -96,7 +165,7 @@ impl Runway {
             positions.sort_by(|(lhs, _), (rhs, _)| {
                 lhs.x.abs().partial_cmp(&rhs.x.abs()).unwrap_or(std::cmp::Ordering::Equal)
             });
-            let mut data = Vec::<Matrix4<f32>>::with_capacity(n as usize);
+            let mut data = Vec::<Matrix4<f32>>::with_capacity(n);
             data.extend(positions.iter().map(|&(offset, scale)| {
                 let angle = Rad(rng.gen_range(Rad::<f32>::zero().0, Rad::<f32>::full_turn().0));
                 Matrix4::from_translation(offset) *
@@ -123,6 +192,26 @@ impl Runway {
                 InstanceTransforms::Fallback(data)
             }
         };
+        // jump ramps in the side lanes, leaving the middle clear
+        let mut ramps = Vec::new();
+        let mut collision = vec![
+            floor_brush(),
+            Brush::new(vec![Plane3D::new( Vector3::unit_x(), WIDTH / 2.0)]),
+            Brush::new(vec![Plane3D::new(-Vector3::unit_x(), WIDTH / 2.0)]),
+        ];
+        for &x in &[-640.0, 640.0] {
+            for &y in &[-LENGTH / 4.0, LENGTH / 4.0] {
+                const RAMP_LENGTH: f32 = 384.0;
+                for &(dy, angle) in &[(-RAMP_LENGTH / 2.0, 0.0), (RAMP_LENGTH / 2.0, 180.0)] {
+                    let transform =
+                        Matrix4::from_translation(Vector3::new(x, y + dy, 0.0)) *
+                        Matrix4::from_angle_z(Deg(angle));
+                    let (ramp, brush) = gen_ramp_section(gl, 256.0, RAMP_LENGTH, Deg(20.0).into(), transform);
+                    ramps.push(ramp);
+                    collision.push(brush);
+                }
+            }
+        }
         Self{
             length: LENGTH, width: WIDTH,
             floor_mesh: gen_box(gl,
@@ -138,25 +227,21 @@ impl Runway {
                 Point3::new( 0.5,  0.5, 2.0),
                 0.5).unwrap(),
             scenery_transforms,
+            ramps,
+            collision,
         }
     }
 }
 
+impl Geometry for Runway {
+    fn trace(&self, start: Point3<f32>, end: Point3<f32>, hull: Hull) -> Trace {
+        self.collision[..].trace(start, end, hull)
+    }
+}
+
 impl Map for Runway {
     fn atmosphere_color(&self) -> Color { Color::new(0.6, 0.8, 1.0, 0.0001) }
     fn interact(&mut self, player: &mut PlayerState) {
-        if player.pos.x - PLAYER_RADIUS < -self.width / 2.0 {
-            player.pos.x = -self.width / 2.0 + PLAYER_RADIUS;
-            if player.vel.x < 0.0 {
-                player.vel.x = 0.0;
-            }
-        }
-        if player.pos.x + PLAYER_RADIUS > self.width / 2.0 {
-            player.pos.x = self.width / 2.0 - PLAYER_RADIUS;
-            if player.vel.x > 0.0 {
-                player.vel.x = 0.0;
-            }
-        }
         if player.pos.y < -self.length / 2.0 {
             player.pos.y += self.length;
         }
@@ -165,8 +250,9 @@ impl Map for Runway {
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn draw(&self,
-        gl: &GlContext,
+        gl: &dyn GlContext,
         program: &Program,
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>)
@@ -195,11 +281,20 @@ impl Map for Runway {
                 ("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(
                     Matrix4::from_translation(Vector3::unit_x() * (self.width + WALL_THICKNESS)/2.0)))),
             ];
+            let ramp_constants = self.ramps.iter()
+                .map(|(_, m)| {
+                    [("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(*m)))]
+                })
+                .collect::<Vec<_>>();
             let mut objects: Vec<(&[_], _, _)> = vec![
                 (&floor_constants, self.floor_mesh.clone(), None),
                 (&wall0_constants, self.wall_mesh.clone(), None),
                 (&wall1_constants, self.wall_mesh.clone(), None),
             ];
+            objects.extend(ramp_constants.iter().zip(self.ramps.iter())
+                .map(|(constants, (mesh, _))| -> (&[_], _, _) {
+                    (constants, mesh.clone(), None)
+                }));
             match &self.scenery_transforms {
                 InstanceTransforms::Instanced(instance_data) => {
                     objects.push((&[], self.scenery_mesh.clone(), Some(instance_data)));
@@ -226,12 +321,15 @@ pub struct Freestyle {
     size: f32,
     floor_mesh: Mesh,
     scenery_mesh: Mesh,
-    scenery_collision: Vec<Box2D>,
     scenery_transforms: InstanceTransforms,
+    ramps: Vec<(Mesh, Matrix4<f32>)>,
+    pools: Vec<(Mesh, Matrix4<f32>)>,
+    collision: Vec<Brush>,
+    volumes: Vec<Volume>,
 }
 
 impl Freestyle {
-    pub fn new(gl: &GlContext) -> Self {
+    pub fn new(gl: &dyn GlContext) -> Self {
         const SIZE: f32 = 8192.0;
         const DENSITY: f32 = 0.0015;
         let n = (SIZE * SIZE * DENSITY * DENSITY) as usize;
@@ -252,16 +350,87 @@ impl Freestyle {
                 positions.push((offset, scale));
             }
         }
-        let mut transforms = Vec::<Matrix4<f32>>::with_capacity(n as usize);
+        // a mix of walkable jump ramps and surf ramps too steep to stand on
+        const RAMP_COUNT: usize = 16;
+        let mut ramps = Vec::with_capacity(RAMP_COUNT);
+        let mut collision = vec![floor_brush()];
+        let mut ramp_positions = Vec::<(Vector3<f32>, f32)>::with_capacity(RAMP_COUNT);
+        while ramp_positions.len() < RAMP_COUNT {
+            let offset = Vector3::new(
+                rng.gen_range(-0.5, 0.5) * SIZE,
+                rng.gen_range(-0.5, 0.5) * SIZE,
+                0.0);
+            let is_surf = rng.gen_bool(0.5);
+            let (length, angle) = if is_surf {
+                (rng.gen_range(256.0, 384.0), Deg(rng.gen_range(50.0, 60.0)))
+            } else {
+                (rng.gen_range(384.0, 768.0), Deg(rng.gen_range(15.0, 30.0)))
+            };
+            let width = rng.gen_range(256.0, 512.0);
+            let radius = Vector2::new(width, length).magnitude() / 2.0;
+            let collides = offset.xy().magnitude() < (radius + PLAYER_RADIUS) ||
+                positions.iter().any(|(other_offset, other_scale)| {
+                    other_offset.xy().distance(offset.xy()) <= radius + 0.707 * other_scale
+                }) ||
+                ramp_positions.iter().any(|(other_offset, other_radius)| {
+                    other_offset.xy().distance(offset.xy()) <= radius + other_radius
+                });
+            if !collides {
+                let angle_z = Rad(rng.gen_range(Rad::<f32>::zero().0, Rad::<f32>::full_turn().0));
+                let transform = Matrix4::from_translation(offset) * Matrix4::from_angle_z(angle_z);
+                let (ramp, brush) = gen_ramp_section(gl, width, length, angle.into(), transform);
+                ramps.push(ramp);
+                collision.push(brush);
+                ramp_positions.push((offset, radius));
+            }
+        }
+        const POOL_COUNT: usize = 4;
+        let mut pools = Vec::new();
+        let mut volumes = Vec::new();
+        let mut pool_positions = Vec::<Vector3<f32>>::with_capacity(POOL_COUNT);
+        let pool_radius = 0.707 * (POOL_SIZE + 2.0 * POOL_WALL_THICKNESS) + LADDER_DEPTH;
+        while pool_positions.len() < POOL_COUNT {
+            let offset = Vector3::new(
+                rng.gen_range(-0.5, 0.5) * SIZE,
+                rng.gen_range(-0.5, 0.5) * SIZE,
+                0.0);
+            let collides = offset.xy().magnitude() < (pool_radius + PLAYER_RADIUS) ||
+                positions.iter().any(|(other_offset, other_scale)| {
+                    other_offset.xy().distance(offset.xy()) <= pool_radius + 0.707 * other_scale
+                }) ||
+                ramp_positions.iter().any(|(other_offset, other_radius)| {
+                    other_offset.xy().distance(offset.xy()) <= pool_radius + other_radius
+                }) ||
+                pool_positions.iter().any(|other_offset| {
+                    other_offset.xy().distance(offset.xy()) <= 2.0 * pool_radius
+                });
+            if !collides {
+                let angle_z = Rad(rng.gen_range(Rad::<f32>::zero().0, Rad::<f32>::full_turn().0));
+                let transform = Matrix4::from_translation(offset) * Matrix4::from_angle_z(angle_z);
+                let (meshes, brushes, pool_volumes) = gen_pool_section(gl, transform);
+                pools.extend(meshes);
+                collision.extend(brushes);
+                volumes.extend(pool_volumes);
+                pool_positions.push(offset);
+            }
+        }
+        let mut transforms = Vec::<Matrix4<f32>>::with_capacity(n);
         transforms.extend(positions.iter().map(|&(offset, scale)| {
+            // sink some boxes into the floor so there's a mix of steps,
+            // ledges which can be jumped onto, and walls
+            let height = match rng.gen_range(0, 3) {
+                0 => rng.gen_range(8.0, STEP_SIZE),
+                1 => rng.gen_range(24.0, 40.0),
+                _ => 2.0 * scale,
+            };
             let angle = Rad(rng.gen_range(Rad::<f32>::zero().0, Rad::<f32>::full_turn().0));
-            Matrix4::from_translation(offset) *
+            Matrix4::from_translation(offset + Vector3::unit_z() * (height - 2.0 * scale)) *
             Matrix4::from_angle_z(angle) *
             Matrix4::from_scale(scale)
         }));
-        let scenery_collision = transforms.iter()
-            .map(|transform| Box2D::from_size_and_transform(1.0, mat_drop_z(*transform)))
-            .collect();
+        collision.extend(transforms.iter().map(|&transform| {
+            Brush::from_box(Point3::new(-0.5, -0.5, 0.0), Point3::new(0.5, 0.5, 2.0), transform)
+        }));
         let scenery_transforms = if gl.webgl2().is_some() {
             let instance = InstanceData{
                 buffer: build_vbo(gl, transforms.as_slice()).unwrap(),
@@ -291,16 +460,16 @@ impl Freestyle {
                 Point3::new(-0.5, -0.5, 0.0),
                 Point3::new( 0.5,  0.5, 2.0),
                 0.5).unwrap(),
-            scenery_collision,
             scenery_transforms,
+            ramps,
+            pools,
+            collision,
+            volumes,
         }
     }
-}
 
-impl Map for Freestyle {
-    fn atmosphere_color(&self) -> Color { Color::new(0.6, 0.8, 1.0, 0.0002) }
-    fn interact(&mut self, player: &mut PlayerState) {
-        for cell_offset in &[
+    fn cell_offsets(&self) -> [Vector2<f32>; 9] {
+        [
             Vector2::new(-self.size, -self.size),
             Vector2::new(       0.0, -self.size),
             Vector2::new( self.size, -self.size),
@@ -310,19 +479,35 @@ impl Map for Freestyle {
             Vector2::new(-self.size,  self.size),
             Vector2::new(       0.0,  self.size),
             Vector2::new( self.size,  self.size),
-        ] {
-            let player_xy = player.pos.xy() + cell_offset;
-            for box2d in self.scenery_collision.iter() {
-                if let Some(offset) = box2d.collide_circle(player_xy, PLAYER_RADIUS) {
-                    if offset.magnitude2() > 0.000_001 {
-                        let dir = offset.normalize().extend(0.0);
-                        player.vel -= dir * dir.dot(player.vel).min(0.0);
-                        player.pos += offset.extend(0.0);
-                    }
-                }
-            }
-        }
+        ]
+    }
+}
 
+impl Geometry for Freestyle {
+    fn trace(&self, start: Point3<f32>, end: Point3<f32>, hull: Hull) -> Trace {
+        // only neighbouring cells near enough to hold anything the trace could touch
+        const CELL_MARGIN: f32 = 1024.0;
+        let bound = self.size / 2.0 + CELL_MARGIN;
+        self.cell_offsets().iter()
+            .map(|cell_offset| cell_offset.extend(0.0))
+            .filter(|offset| {
+                let p = start + offset;
+                p.x.abs() < bound && p.y.abs() < bound
+            })
+            .fold(Trace::default(), |nearest, offset| {
+                nearest.nearest(self.collision[..].trace(start + offset, end + offset, hull))
+            })
+    }
+
+    fn touches(&self, origin: Point3<f32>, hull: Hull, contents: Contents) -> bool {
+        self.cell_offsets().iter()
+            .any(|offset| self.volumes[..].touches(origin + offset.extend(0.0), hull, contents))
+    }
+}
+
+impl Map for Freestyle {
+    fn atmosphere_color(&self) -> Color { Color::new(0.6, 0.8, 1.0, 0.0002) }
+    fn interact(&mut self, player: &mut PlayerState) {
         if player.pos.x < -self.size / 2.0 {
             player.pos.x += self.size;
         }
@@ -337,8 +522,9 @@ impl Map for Freestyle {
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn draw(&self,
-        gl: &GlContext,
+        gl: &dyn GlContext,
         program: &Program,
         view_matrix: &Matrix4<f32>,
         projection_matrix: &Matrix4<f32>)
@@ -369,9 +555,18 @@ impl Map for Freestyle {
             let floor_constants = [
                 ("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(Matrix4::identity()))),
             ];
+            let ramp_constants = self.ramps.iter().chain(self.pools.iter())
+                .map(|(_, m)| {
+                    [("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(*m)))]
+                })
+                .collect::<Vec<_>>();
             let mut objects: Vec<(&[_], _, _)> = vec![
                 (&floor_constants, self.floor_mesh.clone(), None),
             ];
+            objects.extend(ramp_constants.iter().zip(self.ramps.iter().chain(self.pools.iter()))
+                .map(|(constants, (mesh, _))| -> (&[_], _, _) {
+                    (constants, mesh.clone(), None)
+                }));
             match &self.scenery_transforms {
                 InstanceTransforms::Instanced(instance_data) => {
                     objects.push((&[], self.scenery_mesh.clone(), Some(instance_data)));
@@ -55,7 +55,7 @@ impl Color {
             1.055 * x.powf(1.0/2.4) - 0.055
         }
     }
-    pub fn to_srgb(&self) -> Self {
+    pub fn to_srgb(self) -> Self {
         Self{
             r: Self::linear_to_srgb(self.r),
             g: Self::linear_to_srgb(self.g),
@@ -71,11 +71,11 @@ where
     T: 'static + Sized + Copy + Send + Sync
 {
     let start = data.as_ptr();
-    let size  = data.len() * std::mem::size_of::<T>();
+    let size  = std::mem::size_of_val(data);
     unsafe { std::slice::from_raw_parts(start as *const u8, size) }
 }
 
-fn build_shader(gl: &GlContext, type_: u32, source: &str)
+fn build_shader(gl: &dyn GlContext, type_: u32, source: &str)
     -> Option<WebGlShader>
 {
     let shader = gl.create_shader(type_)?;
@@ -95,23 +95,23 @@ fn build_shader(gl: &GlContext, type_: u32, source: &str)
     }
 }
 
-fn link_program(gl: &GlContext, program: &WebGlProgram) -> Result<(), ()> {
-    gl.link_program(&program);
-    let status = gl.get_program_parameter(&program,
+fn link_program(gl: &dyn GlContext, program: &WebGlProgram) -> Result<(), ()> {
+    gl.link_program(program);
+    let status = gl.get_program_parameter(program,
         WebGlRenderingContext::LINK_STATUS);
 
     if let Some(true) = status.as_bool() {
         Ok(())
     } else {
         error("failed to link program!");
-        if let Some(log_) = gl.get_program_info_log(&program) {
+        if let Some(log_) = gl.get_program_info_log(program) {
             log(log_.as_str());
         }
         Err(())
     }
 }
 
-fn build_program(gl: &GlContext, source_vs: &str, source_fs: &str)
+fn build_program(gl: &dyn GlContext, source_vs: &str, source_fs: &str)
     -> Option<WebGlProgram>
 {
     let vs = build_shader(gl, WebGlRenderingContext::VERTEX_SHADER  , source_vs)?;
@@ -123,7 +123,7 @@ fn build_program(gl: &GlContext, source_vs: &str, source_fs: &str)
     Some(program)
 }
 
-pub fn build_vbo<T>(gl: &GlContext, data: &[T]) -> Option<WebGlBuffer>
+pub fn build_vbo<T>(gl: &dyn GlContext, data: &[T]) -> Option<WebGlBuffer>
 where
     T: 'static + Sized + Copy + Send + Sync
 {
@@ -180,7 +180,7 @@ pub struct Program {
 }
 
 impl Program {
-    pub fn wrap(gl: &GlContext, program: WebGlProgram) -> Self {
+    pub fn wrap(gl: &dyn GlContext, program: WebGlProgram) -> Self {
         let attrib_count = gl.get_program_parameter(&program,
             WebGlRenderingContext::ACTIVE_ATTRIBUTES).as_f64().unwrap() as u32;
         let attributes = (0..attrib_count)
@@ -213,15 +213,15 @@ impl Program {
         Self{program, attributes, uniforms}
     }
 
-    pub fn from_source(gl: &GlContext, source_vs: &str, source_fs: &str) -> Option<Self> {
+    pub fn from_source(gl: &dyn GlContext, source_vs: &str, source_fs: &str) -> Option<Self> {
         Some(Self::wrap(gl, build_program(gl, source_vs, source_fs)?))
     }
 
-    pub fn use_program(&self, gl: &GlContext) {
+    pub fn use_program(&self, gl: &dyn GlContext) {
         gl.use_program(Some(&self.program));
     }
 
-    fn assign_vertex_attribs<'a, VertexAttribs>(&self, gl: &GlContext, vertex_attribs: VertexAttribs)
+    fn assign_vertex_attribs<'a, VertexAttribs>(&self, gl: &dyn GlContext, vertex_attribs: VertexAttribs)
     where
         VertexAttribs: IntoIterator<Item = &'a VertexAttrib>
     {
@@ -261,7 +261,7 @@ impl Program {
         }
     }
 
-    fn clear_vertex_attribs(&self, gl: &GlContext) {
+    fn clear_vertex_attribs(&self, gl: &dyn GlContext) {
         for (attrib, location) in &self.attributes {
             let (_, _, _, n) = attrib.type_info();
             for i in 0..n {
@@ -270,7 +270,7 @@ impl Program {
         }
     }
 
-    pub fn set_attribute(&self, gl: &GlContext, name: &str, value: &ConstantValue) {
+    pub fn set_attribute(&self, gl: &dyn GlContext, name: &str, value: &ConstantValue) {
         if let Some((attrib, location)) = self.attributes.iter()
             .find(|(attrib, _)| attrib.name == name)
         {
@@ -314,7 +314,7 @@ impl Program {
         }
     }
 
-    pub fn set_uniform(&self, gl: &GlContext, name: &str, value: &ConstantValue) {
+    pub fn set_uniform(&self, gl: &dyn GlContext, name: &str, value: &ConstantValue) {
         if let Some((uniform, location)) = self.uniforms.iter()
             .find(|(uniform, _)| uniform.name == name)
         {
@@ -356,10 +356,10 @@ impl Program {
         }
     }
 
-    pub fn set_constant(&self, gl: &GlContext, name: &str, value: &Constant) {
+    pub fn set_constant(&self, gl: &dyn GlContext, name: &str, value: &Constant) {
         match value {
-            Constant::Uniform     (value) => { self.set_uniform  (gl, name, &value) }
-            Constant::VertexAttrib(value) => { self.set_attribute(gl, name, &value) }
+            Constant::Uniform     (value) => { self.set_uniform  (gl, name, value) }
+            Constant::VertexAttrib(value) => { self.set_attribute(gl, name, value) }
         }
     }
 }
@@ -398,7 +398,7 @@ pub struct Mesh {
 }
 
 impl Mesh {
-    pub fn from_vertices<V: VertexLayout>(gl: &GlContext, draw_mode: u32, data: &[V])
+    pub fn from_vertices<V: VertexLayout>(gl: &dyn GlContext, draw_mode: u32, data: &[V])
         -> Option<Self>
     {
         let vertices = build_vbo(gl, data)?;
@@ -419,7 +419,7 @@ pub struct InstanceData {
 }
 
 pub fn draw_pass<'a, Constants, Meshes, MeshConstants>(
-    gl: &GlContext,
+    gl: &dyn GlContext,
     program: &Program,
     constants: Constants,
     meshes: Meshes,
@@ -433,7 +433,7 @@ where
 
     for constant in constants.into_iter() {
         let (name, value) = constant;
-        program.set_constant(gl, name, &value);
+        program.set_constant(gl, name, value);
     }
 
     for mesh in meshes.into_iter() {
@@ -441,7 +441,7 @@ where
 
         for constant in constants.into_iter() {
             let (name, value) = constant;
-            program.set_constant(gl, name, &value);
+            program.set_constant(gl, name, value);
         }
 
         gl.bind_buffer(
@@ -542,7 +542,7 @@ impl VertexLayout for MeshVertex {
     }
 }
 
-pub fn gen_box(gl: &GlContext, min: Point3<f32>, max: Point3<f32>, uv_scale: f32) -> Option<Mesh> {
+pub fn gen_box(gl: &dyn GlContext, min: Point3<f32>, max: Point3<f32>, uv_scale: f32) -> Option<Mesh> {
     fn face_uv(min: Point3<f32>, max: Point3<f32>, uv_scale: f32, front: bool, flip_z: bool) -> Vec<MeshVertex> {
         let mut vs = Vec::new();
         let z = if front != flip_z { max.z } else { min.z };
@@ -584,6 +584,40 @@ pub fn gen_box(gl: &GlContext, min: Point3<f32>, max: Point3<f32>, uv_scale: f32
         .as_slice())
 }
 
+// wedge rising along +y, from min.z at min.y to max.z at max.y (matches Brush::from_ramp)
+pub fn gen_ramp(gl: &dyn GlContext, min: Point3<f32>, max: Point3<f32>, uv_scale: f32) -> Option<Mesh> {
+    fn tri(vs: &mut Vec<MeshVertex>, ps: [Point3<f32>; 3], uv_scale: f32) {
+        let norm = (ps[1] - ps[0]).cross(ps[2] - ps[0]).normalize();
+        let t = if norm.x.abs() > 0.9 { Vector3::unit_y() } else { Vector3::unit_x() };
+        let s = norm.cross(t).normalize();
+        let t = s.cross(norm);
+        for &p in &ps {
+            let uv = Point2::new(p.to_vec().dot(t), p.to_vec().dot(s)) / uv_scale;
+            vs.push(MeshVertex::new(p, norm, uv));
+        }
+    }
+
+    let p = |x: f32, y: f32, z: f32| Point3::new(x, y, z);
+    let (x0, y0, z0) = (min.x, min.y, min.z);
+    let (x1, y1, z1) = (max.x, max.y, max.z);
+
+    let mut vs = Vec::new();
+    // slope
+    tri(&mut vs, [p(x0, y0, z0), p(x1, y0, z0), p(x1, y1, z1)], uv_scale);
+    tri(&mut vs, [p(x0, y0, z0), p(x1, y1, z1), p(x0, y1, z1)], uv_scale);
+    // bottom
+    tri(&mut vs, [p(x0, y0, z0), p(x1, y1, z0), p(x1, y0, z0)], uv_scale);
+    tri(&mut vs, [p(x0, y0, z0), p(x0, y1, z0), p(x1, y1, z0)], uv_scale);
+    // back
+    tri(&mut vs, [p(x1, y1, z0), p(x0, y1, z0), p(x0, y1, z1)], uv_scale);
+    tri(&mut vs, [p(x1, y1, z0), p(x0, y1, z1), p(x1, y1, z1)], uv_scale);
+    // sides
+    tri(&mut vs, [p(x0, y0, z0), p(x0, y1, z1), p(x0, y1, z0)], uv_scale);
+    tri(&mut vs, [p(x1, y0, z0), p(x1, y1, z0), p(x1, y1, z1)], uv_scale);
+
+    Mesh::from_vertices(gl, WebGlRenderingContext::TRIANGLES, vs.as_slice())
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct HudVertex {
@@ -621,7 +655,7 @@ impl VertexLayout for HudVertex {
     }
 }
 
-pub fn gen_hud_quad(gl: &GlContext, min: Point2<f32>, max: Point2<f32>) -> Option<Mesh> {
+pub fn gen_hud_quad(gl: &dyn GlContext, min: Point2<f32>, max: Point2<f32>) -> Option<Mesh> {
     let vs = [
         HudVertex::from_scalars(min.x, min.y),
         HudVertex::from_scalars(max.x, min.y),
@@ -721,7 +755,7 @@ impl WarpEffect {
             unsafe { ptr::write(dst.as_mut_ptr(), src) };
         };
 
-        let particles = unsafe { mem::transmute::<_, [WebGlBuffer; WARP_EFFECT_FRAMES]>(particles) };
+        let particles = unsafe { mem::transmute::<[MaybeUninit<WebGlBuffer>; WARP_EFFECT_FRAMES], [WebGlBuffer; WARP_EFFECT_FRAMES]>(particles) };
 
         let data = (0..capacity)
             .map(|_| {
@@ -756,7 +790,7 @@ impl WarpEffect {
         }
 
         for vbo in &particles[1..] {
-            gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&vbo));
+            gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(vbo));
             gl.buffer_data_with_i32(WebGl2RenderingContext::ARRAY_BUFFER,
                 (capacity as usize * mem::size_of::<Point3<f32>>()) as i32,
                 WebGl2RenderingContext::DYNAMIC_COPY);
@@ -821,7 +855,7 @@ impl WarpEffect {
         vel: Vector3<f32>, dt: f32)
     {
         let n = {
-            let u = ((vel.magnitude() - WARP_UPS_MIN) / (WARP_UPS_MAX - WARP_UPS_MIN)).min(1.0).max(0.0);
+            let u = ((vel.magnitude() - WARP_UPS_MIN) / (WARP_UPS_MAX - WARP_UPS_MIN)).clamp(0.0, 1.0);
             let n = (self.capacity as f32 * u * u) as i32;
             if n < 1 {
                 return;
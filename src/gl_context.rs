@@ -20,6 +20,8 @@
  * IN THE SOFTWARE.
 */
 
+#![allow(dead_code)]
+
 use wasm_bindgen::JsCast;
 use web_sys::{
     HtmlCanvasElement,
@@ -115,13 +117,13 @@ impl_webgl_trait!{
 }
 
 impl VersionedContext for WebGlRenderingContext {
-    fn webgl1(&self) -> Option<&WebGlRenderingContext> { Some(&self) }
+    fn webgl1(&self) -> Option<&WebGlRenderingContext> { Some(self) }
     fn webgl2(&self) -> Option<&WebGl2RenderingContext> { None }
 }
 
 impl VersionedContext for WebGl2RenderingContext {
     fn webgl1(&self) -> Option<&WebGlRenderingContext> { None }
-    fn webgl2(&self) -> Option<&WebGl2RenderingContext> { Some(&self) }
+    fn webgl2(&self) -> Option<&WebGl2RenderingContext> { Some(self) }
 }
 
 pub enum GlVersionRequirement {
@@ -161,7 +163,7 @@ impl AnyGlContext {
         }
     }
 
-    pub fn gl(&self) -> &GlContext {
+    pub fn gl(&self) -> &dyn GlContext {
         match self {
             AnyGlContext::Gl1(gl) => gl,
             AnyGlContext::Gl2(gl) => gl,
@@ -17,10 +17,10 @@
 
 use cgmath::Rad;
 use serde::{Serialize, Deserialize};
+use strafe_core::input::KeyState;
 use web_sys::Storage;
 
 use std::fmt;
-use std::ops;
 
 #[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct MouseSettings {
@@ -60,92 +60,11 @@ pub enum KeyCode {
     KeyS,
     KeyD,
     KeyF,
+    KeyC,
+    KeyE,
+    Shift,
     Space,
-}
-
-#[derive(Copy, Clone, Default, Eq, PartialEq)]
-pub struct KeyState {
-    pub key_w: bool,
-    pub key_a: bool,
-    pub key_s: bool,
-    pub key_d: bool,
-    pub key_f: bool,
-    pub space: bool,
-}
-
-pub const KEYS_DEFAULT: KeyState = KeyState{
-    key_w: false,
-    key_a: false,
-    key_s: false,
-    key_d: false,
-    key_f: false,
-    space: false,
-};
-
-impl KeyState {
-    pub fn is_side_strafe(self) -> bool {
-         (self.key_a || self.key_d) &&
-        !(self.key_w || self.key_s)
-    }
-
-    pub fn pressed(self, previous: KeyState) -> KeyState {
-        self & !previous
-    }
-
-    pub fn released(self, previous: KeyState) -> KeyState {
-        !self & previous
-    }
-
-    pub fn set_mapped(&mut self, binds: &KeyBinds, button: Button, pressed: bool) {
-        if binds.key_w == button { self.key_w = pressed; }
-        if binds.key_a == button { self.key_a = pressed; }
-        if binds.key_s == button { self.key_s = pressed; }
-        if binds.key_d == button { self.key_d = pressed; }
-        if binds.key_f == button { self.key_f = pressed; }
-        if binds.space == button { self.space = pressed; }
-    }
-}
-
-impl ops::Not for KeyState {
-    type Output = KeyState;
-    fn not(self) -> KeyState {
-        KeyState{
-            key_w: !self.key_w,
-            key_a: !self.key_a,
-            key_s: !self.key_s,
-            key_d: !self.key_d,
-            key_f: !self.key_f,
-            space: !self.space,
-        }
-    }
-}
-
-impl ops::BitAnd for KeyState {
-    type Output = KeyState;
-    fn bitand(self, other: KeyState) -> KeyState {
-        KeyState{
-            key_w: self.key_w & other.key_w,
-            key_a: self.key_a & other.key_a,
-            key_s: self.key_s & other.key_s,
-            key_d: self.key_d & other.key_d,
-            key_f: self.key_f & other.key_f,
-            space: self.space & other.space,
-        }
-    }
-}
-
-impl ops::BitOr for KeyState {
-    type Output = KeyState;
-    fn bitor(self, other: KeyState) -> KeyState {
-        KeyState{
-            key_w: self.key_w | other.key_w,
-            key_a: self.key_a | other.key_a,
-            key_s: self.key_s | other.key_s,
-            key_d: self.key_d | other.key_d,
-            key_f: self.key_f | other.key_f,
-            space: self.space | other.space,
-        }
-    }
+    MouseLeft,
 }
 
 #[derive(PartialEq, Serialize, Deserialize)]
@@ -170,9 +89,22 @@ pub struct KeyBinds {
     pub key_s: Button,
     pub key_d: Button,
     pub key_f: Button,
+    #[serde(default = "default_key_c")]
+    pub key_c: Button,
+    #[serde(default = "default_key_e")]
+    pub key_e: Button,
+    #[serde(default = "default_shift")]
+    pub shift: Button,
     pub space: Button,
+    #[serde(default = "default_mouse_left")]
+    pub mouse_left: Button,
 }
 
+fn default_key_c() -> Button { Button::Key("KeyC"     .to_string()) }
+fn default_key_e() -> Button { Button::Key("KeyE"     .to_string()) }
+fn default_shift() -> Button { Button::Key("ShiftLeft".to_string()) }
+fn default_mouse_left() -> Button { Button::Mouse(0) }
+
 impl KeyBinds {
     pub fn load(storage: &Storage, key: &str) -> Result<Self, ()> {
         let data = storage.get_item(key)
@@ -186,6 +118,19 @@ impl KeyBinds {
         storage.set_item(key, data.as_str()).map_err(|_| ())
     }
 
+    pub fn set_mapped(&self, key_state: &mut KeyState, button: Button, pressed: bool) {
+        if self.key_w == button { key_state.key_w = pressed; }
+        if self.key_a == button { key_state.key_a = pressed; }
+        if self.key_s == button { key_state.key_s = pressed; }
+        if self.key_d == button { key_state.key_d = pressed; }
+        if self.key_f == button { key_state.key_f = pressed; }
+        if self.key_c == button { key_state.key_c = pressed; }
+        if self.key_e == button { key_state.key_e = pressed; }
+        if self.shift == button { key_state.shift = pressed; }
+        if self.space == button { key_state.space = pressed; }
+        if self.mouse_left == button { key_state.mouse_left = pressed; }
+    }
+
     pub fn button(&self, target: KeyCode) -> &Button {
         match target {
             KeyCode::KeyW  => &self.key_w,
@@ -193,7 +138,11 @@ impl KeyBinds {
             KeyCode::KeyS  => &self.key_s,
             KeyCode::KeyD  => &self.key_d,
             KeyCode::KeyF  => &self.key_f,
+            KeyCode::KeyC  => &self.key_c,
+            KeyCode::KeyE  => &self.key_e,
+            KeyCode::Shift => &self.shift,
             KeyCode::Space => &self.space,
+            KeyCode::MouseLeft => &self.mouse_left,
         }
     }
 
@@ -204,7 +153,11 @@ impl KeyBinds {
             KeyCode::KeyS  => &mut self.key_s,
             KeyCode::KeyD  => &mut self.key_d,
             KeyCode::KeyF  => &mut self.key_f,
+            KeyCode::KeyC  => &mut self.key_c,
+            KeyCode::KeyE  => &mut self.key_e,
+            KeyCode::Shift => &mut self.shift,
             KeyCode::Space => &mut self.space,
+            KeyCode::MouseLeft => &mut self.mouse_left,
         };
         *target = button;
     }
@@ -218,7 +171,11 @@ impl Default for KeyBinds {
             key_s: Button::Key("KeyS" .to_string()),
             key_d: Button::Key("KeyD" .to_string()),
             key_f: Button::Key("KeyF" .to_string()),
+            key_c: default_key_c(),
+            key_e: default_key_e(),
+            shift: default_shift(),
             space: Button::Key("Space".to_string()),
+            mouse_left: default_mouse_left(),
         }
     }
 }
\ No newline at end of file
@@ -19,6 +19,7 @@ extern crate cgmath;
 extern crate console_error_panic_hook;
 extern crate js_sys;
 extern crate rand;
+extern crate strafe_core;
 extern crate wasm_bindgen;
 extern crate web_sys;
 
@@ -30,12 +31,14 @@ use cgmath::{
     Matrix4,
     PerspectiveFov,
     Point2,
+    Point3,
     Rad,
     Vector2,
 };
 use wasm_bindgen::JsCast;
 use web_sys::{
     Element,
+    FileReader,
     KeyboardEvent,
     MouseEvent,
     Storage,
@@ -52,20 +55,18 @@ extern {
     fn error(_: &str);
 }
 
-mod collision;
 mod env;
 mod gl_context;
 mod gfx;
 mod input;
-mod player;
-mod ai;
+mod settings;
 mod ui;
 
-use ai::{StrafeBot, StrafeConfig};
 use env::{Map, Freestyle, Runway};
 use gl_context::{AnyGlContext, GlVersionRequirement};
 use gfx::{
     draw_pass,
+    gen_box,
     gen_hud_quad,
     Mesh,
     Program,
@@ -77,17 +78,35 @@ use input::{
     Button,
     KeyBinds,
     KeyCode,
-    KeyState,
     MouseSettings,
 };
-use player::{
+use settings::{MovementPreset, MovementSettings, SimSettings, TickRate};
+use strafe_core::ai::{Assist, Skill, StartRoutine, StrafeBot, StrafeConfig};
+use strafe_core::cfg::{apply_cfg, Dialect};
+use strafe_core::input::KeyState;
+use strafe_core::replay::{Ghost, TickInput};
+use strafe_core::theory::Strafe;
+use strafe_core::weapon::{Arsenal, Weapon, WEAPON_PLASMA, WEAPON_ROCKET};
+use strafe_core::player::{
+    BhopCap,
+    CrouchSlide,
+    Dash,
+    DoubleJump,
+    Integrator,
+    JumpMode,
     Kinematics,
+    LandingFriction,
     Movement,
+    MOVE_CPMA_LIKE,
     MOVE_HYBRID,
     MOVE_QW_LIKE,
+    MOVE_SOURCE_LIKE,
     MOVE_VQ3_LIKE,
+    MOVE_WARSOW_LIKE,
     PlayerState,
+    PLAYER_HEIGHT,
     PLAYER_RADIUS,
+    WallJump,
 };
 use ui::{get_ui, UI};
 
@@ -100,6 +119,15 @@ const UNITS_PER_KM: f32 = 39370.1;
 const MPH_PER_UPS: f32 = 3600.0 / UNITS_PER_MILE;
 const KPH_PER_UPS: f32 = 3600.0 / UNITS_PER_KM;
 
+const BOT_STARTS: [(&str, StartRoutine); 6] = [
+    ("standing-jump"  , StartRoutine::STANDING_JUMP  ),
+    ("circle-jump-90" , StartRoutine::CIRCLE_JUMP_90 ),
+    ("circle-jump-150", StartRoutine::CIRCLE_JUMP_150),
+    ("circle-jump-180", StartRoutine::CIRCLE_JUMP_180),
+    ("circle-jump-270", StartRoutine::CIRCLE_JUMP_270),
+    ("prestrafe"      , StartRoutine::PRESTRAFE      ),
+];
+
 #[derive(Copy, Clone)]
 enum TimedStage {
     Waiting(f32),
@@ -195,6 +223,8 @@ varying vec3 f_norm;
 varying vec2 f_uv;
 
 uniform vec4 fog_color;
+// 0 for the world, 1 for the comparison ghost
+uniform float ghost;
 
 vec3 to_srgb(vec3 x) {
     return mix(12.92 * x, 1.055 * pow(x, vec3(1.0/2.4)) - 0.055, step(0.0031308, x));
@@ -212,6 +242,7 @@ void main() {
     }
 
     vec3 light = normalize(vec3(1.0, 2.0, 3.0));
+    color = mix(color, vec3(0.1, 0.4, 0.8), ghost);
     color *= 0.5 * dot(norm, light) + 0.5;
 
     vec4 fog = fog_color;
@@ -222,7 +253,7 @@ void main() {
     // at least fog will be gamma-correct...
     color = to_srgb(mix(color, fog.rgb, fog.a));
 
-    gl_FragColor = vec4(color, 1.0);
+    gl_FragColor = vec4(color, 1.0 - 0.5 * ghost);
 }
 ";
 
@@ -255,21 +286,25 @@ precision highp float;
 
 uniform vec2 move_dir;
 uniform float warp_factor;
+uniform float cos_min;
+uniform float cos_optimal;
+uniform float cos_max;
 
 varying vec2 target_dir;
 varying float x_axis;
 
 void main() {
     vec2 dir = normalize(target_dir);
-    float accel = 0.0;
-    if (warp_factor * dot(dir, move_dir) < 0.999) {
-        accel = dot(dir, move_dir);
-    }
+    float c = dot(dir, move_dir);
     vec4 target_color = vec4(0.0);
     vec4 cursor_color = vec4(0.0);
-    if (accel <= 0.0) {
-        target_color = vec4(1.0, 0.0, 0.0, -accel);
-    } else {
+    if (c < cos_max) {
+        target_color = vec4(1.0, 0.0, 0.0, (cos_max - c) / max(cos_max + 1.0, 0.001));
+    } else if (c < cos_min) {
+        // brightest at the optimal angle
+        float accel = c < cos_optimal
+            ? (c - cos_max) / max(cos_optimal - cos_max, 0.001)
+            : (cos_min - c) / max(cos_min - cos_optimal, 0.001);
         target_color = vec4(0.0, 1.0, 0.0, accel);
         cursor_color.rgb = vec3(1.0);
     }
@@ -288,10 +323,15 @@ struct Application {
     perspective: PerspectiveFov::<f32>,
     player_state: PlayerState,
     kinematics: Kinematics,
+    movement_presets: Vec<MovementPreset>,
+    weapon: Option<Weapon>,
+    arsenal: Arsenal,
+    ghost: Option<Ghost>,
     strafe_bot: Option<StrafeBot>,
     auto_hop : bool,
     auto_move: bool,
     auto_turn: bool,
+    assist: Option<Assist>,
     menu_shown: bool,
     have_pointer: bool,
     input_rotation: (Rad<f32>, Rad<f32>),
@@ -305,13 +345,16 @@ struct Application {
     bot_key_history: KeyState,
     last_frame_us: u32,
     tick_remainder_s: f32,
+    sim_settings: SimSettings,
     framerate: f32,
     map_option: MapOption,
-    map: Box<Map>,
+    map: Box<dyn Map>,
     warp_effect: Option<WarpEffect>,
     main_program: Program,
     hud_program: Program,
     hud_mesh: Mesh,
+    projectile_mesh: Mesh,
+    ghost_mesh: Mesh,
 }
 
 impl Application {
@@ -330,6 +373,16 @@ impl Application {
             .and_then(|mouse_settings| mouse_settings)
             .unwrap_or_default();
 
+        let sim_settings = storage.as_ref()
+            .map(|storage| { SimSettings::load(storage, "sim_settings").ok() })
+            .and_then(|sim_settings| sim_settings)
+            .unwrap_or_default();
+
+        let movement_settings = storage.as_ref()
+            .map(|storage| { MovementSettings::load(storage, "movement_settings").ok() })
+            .and_then(|movement_settings| movement_settings)
+            .unwrap_or_default();
+
         ui.mouse_flip_x.set_checked(mouse_settings.flip_x);
         ui.mouse_flip_y.set_checked(mouse_settings.flip_y);
 
@@ -365,6 +418,18 @@ impl Application {
             Point2::new( 1.0,  0.0125))
             .expect("failed to build box VBO");
 
+        let projectile_mesh = gen_box(gl.gl(),
+            Point3::new(-4.0, -4.0, -4.0),
+            Point3::new( 4.0,  4.0,  4.0),
+            8.0)
+            .expect("failed to build projectile VBO");
+
+        let ghost_mesh = gen_box(gl.gl(),
+            Point3::new(-PLAYER_RADIUS, -PLAYER_RADIUS, 0.0),
+            Point3::new( PLAYER_RADIUS,  PLAYER_RADIUS, PLAYER_HEIGHT),
+            16.0)
+            .expect("failed to build ghost VBO");
+
         let mut app = Application{
             ui, gl, storage,
             stage: None,
@@ -375,11 +440,16 @@ impl Application {
                 far: 100_000.0,
             },
             player_state: PlayerState::default(),
-            kinematics: MOVE_VQ3_LIKE,
+            kinematics: movement_settings.kinematics,
+            movement_presets: movement_settings.presets,
+            weapon: None,
+            arsenal: Arsenal::default(),
+            ghost: None,
             strafe_bot: Some(StrafeBot::new(StrafeConfig::STANDARD)),
             auto_hop : true,
             auto_move: true,
             auto_turn: true,
+            assist: None,
             menu_shown: true,
             have_pointer: false,
             input_rotation: (Rad::zero(), Rad::zero()),
@@ -393,6 +463,7 @@ impl Application {
             bot_key_history: KeyState::default(),
             last_frame_us: 0,
             tick_remainder_s: 0.0,
+            sim_settings,
             framerate: 0.0,
             map_option: MapOption::Runway,
             map,
@@ -400,11 +471,16 @@ impl Application {
             main_program,
             hud_program,
             hud_mesh,
+            projectile_mesh,
+            ghost_mesh,
         };
 
         app.update_mouse_sensitivity();
         app.update_key_binds();
+        app.validate_movement();
         app.update_movement_display();
+        app.update_preset_display();
+        app.update_tick_rate_display();
         app.update_bot_display();
 
         app
@@ -413,6 +489,9 @@ impl Application {
     fn set_stage(&mut self, stage: Option<TutorialStage>) {
         self.stage = stage;
         self.player_state.reset();
+        self.arsenal.reset();
+        self.restart_ghost();
+        self.assist = None;
         let dialog = &mut self.ui.dialog.dyn_ref::<web_sys::Node>().unwrap();
         match self.stage {
             Some(stage) => {
@@ -550,7 +629,11 @@ impl Application {
             KeyCode::KeyS,
             KeyCode::KeyD,
             KeyCode::KeyF,
+            KeyCode::KeyC,
+            KeyCode::KeyE,
+            KeyCode::Shift,
             KeyCode::Space,
+            KeyCode::MouseLeft,
         ].iter().for_each(|&target| {
             self.update_key_bind_text(target)
         });
@@ -571,7 +654,7 @@ impl Application {
                 }
             }
         } else {
-            self.input_key_state.set_mapped(&self.key_binds, button, pressed);
+            self.key_binds.set_mapped(&mut self.input_key_state, button, pressed);
         }
     }
 
@@ -595,6 +678,30 @@ impl Application {
         }
     }
 
+    fn save_sim_settings(&self) {
+        if let Some(storage) = &self.storage {
+            if self.sim_settings.save(storage, "sim_settings").is_err() {
+                error("failed to save simulation settings");
+            }
+        } else {
+            warn("cannot save simulation settings; no local_storage");
+        }
+    }
+
+    fn save_movement_settings(&self) {
+        let movement_settings = MovementSettings{
+            kinematics: self.kinematics.clone(),
+            presets: self.movement_presets.clone(),
+        };
+        if let Some(storage) = &self.storage {
+            if movement_settings.save(storage, "movement_settings").is_err() {
+                error("failed to save movement settings");
+            }
+        } else {
+            warn("cannot save movement settings; no local_storage");
+        }
+    }
+
     fn update_mouse_sensitivity(&mut self) {
         let sense = self.mouse_settings.scale;
         self.ui.mouse_input.set_value_as_number(f64::from(sense.0.log2()));
@@ -609,7 +716,10 @@ impl Application {
             MapOption::Runway    => Box::new(Runway   ::new(self.gl.gl())),
             MapOption::Freestyle => Box::new(Freestyle::new(self.gl.gl())),
         };
-        if map == MapOption::Runway && self.stage.is_none() {
+        self.player_state.reset();
+        self.arsenal.reset();
+        self.restart_ghost();
+        if self.stage.is_none() {
             show(self.ui.menu_bot.dyn_ref::<Element>().unwrap());
         } else {
             hide(self.ui.menu_bot.dyn_ref::<Element>().unwrap());
@@ -623,6 +733,14 @@ impl Application {
         self.ui.move_friction    .set_value_as_number(f64::from(self.kinematics.friction.friction    ));
         self.ui.move_ground_speed.set_value_as_number(f64::from(self.kinematics.move_ground.max_speed));
         self.ui.move_ground_accel.set_value_as_number(f64::from(self.kinematics.move_ground.accel    ));
+        self.ui.move_walk_speed  .set_value_as_number(f64::from(self.kinematics.move_walk.max_speed  ));
+        self.ui.move_walk_accel  .set_value_as_number(f64::from(self.kinematics.move_walk.accel      ));
+        self.ui.move_crouch_speed.set_value_as_number(f64::from(self.kinematics.move_crouch.max_speed));
+        self.ui.move_crouch_accel.set_value_as_number(f64::from(self.kinematics.move_crouch.accel    ));
+        self.ui.move_swim_speed  .set_value_as_number(f64::from(self.kinematics.water.movement.max_speed));
+        self.ui.move_swim_accel  .set_value_as_number(f64::from(self.kinematics.water.movement.accel    ));
+        self.ui.move_swim_fric   .set_value_as_number(f64::from(self.kinematics.water.friction          ));
+        self.ui.move_climb_speed .set_value_as_number(f64::from(self.kinematics.ladder.climb_speed      ));
         self.ui.move_air_speed   .set_value_as_number(f64::from(self.kinematics.move_air.max_speed   ));
         self.ui.move_air_accel   .set_value_as_number(f64::from(self.kinematics.move_air.accel       ));
         if let Some(move_air_turning) = self.kinematics.move_air_turning {
@@ -638,9 +756,149 @@ impl Application {
             self.ui.move_turn_speed  .set_value("");
             self.ui.move_turn_accel  .set_value("");
         }
+        if let Some(move_air_stopping) = self.kinematics.move_air_stopping {
+            self.ui.move_stop_enabled.set_checked(true);
+            self.ui.move_stop_speed  .set_disabled(false);
+            self.ui.move_stop_accel  .set_disabled(false);
+            self.ui.move_stop_speed  .set_value_as_number(f64::from(move_air_stopping.max_speed));
+            self.ui.move_stop_accel  .set_value_as_number(f64::from(move_air_stopping.accel    ));
+        } else {
+            self.ui.move_stop_enabled.set_checked(false);
+            self.ui.move_stop_speed  .set_disabled(true);
+            self.ui.move_stop_accel  .set_disabled(true);
+            self.ui.move_stop_speed  .set_value("");
+            self.ui.move_stop_accel  .set_value("");
+        }
+        self.ui.move_jump_mode.set_value(match self.kinematics.jump_mode {
+            JumpMode::Hold           => "hold",
+            JumpMode::RequireRelease => "require-release",
+            JumpMode::Buffer(..)     => "buffer",
+        });
+        if let JumpMode::Buffer(window_s) = self.kinematics.jump_mode {
+            self.ui.move_jump_buffer.set_disabled(false);
+            self.ui.move_jump_buffer.set_value_as_number(f64::from(window_s * 1000.0));
+        } else {
+            self.ui.move_jump_buffer.set_disabled(true);
+            self.ui.move_jump_buffer.set_value("");
+        }
+        self.ui.move_land_mode.set_value(match self.kinematics.landing_friction {
+            LandingFriction::Immediate        => "immediate",
+            LandingFriction::FrictionFree(..) => "friction-free",
+        });
+        if let LandingFriction::FrictionFree(ticks) = self.kinematics.landing_friction {
+            self.ui.move_land_ticks.set_disabled(false);
+            self.ui.move_land_ticks.set_value_as_number(f64::from(ticks));
+        } else {
+            self.ui.move_land_ticks.set_disabled(true);
+            self.ui.move_land_ticks.set_value("");
+        }
+        self.ui.move_integrator.set_value(match self.kinematics.integrator {
+            Integrator::Euler           => "euler",
+            Integrator::HalfStepGravity => "half-step-gravity",
+        });
+        self.ui.move_q3_fidelity.set_checked(self.kinematics.q3_fidelity);
+        self.ui.move_ramp_jump  .set_checked(self.kinematics.ramp_jump  );
+        if let Some(double_jump) = self.kinematics.double_jump {
+            self.ui.move_dj_enable .set_checked(true);
+            self.ui.move_dj_time   .set_disabled(false);
+            self.ui.move_dj_impulse.set_disabled(false);
+            self.ui.move_dj_time   .set_value_as_number(f64::from(double_jump.window_s * 1000.0));
+            self.ui.move_dj_impulse.set_value_as_number(f64::from(double_jump.impulse         ));
+        } else {
+            self.ui.move_dj_enable .set_checked(false);
+            self.ui.move_dj_time   .set_disabled(true);
+            self.ui.move_dj_impulse.set_disabled(true);
+            self.ui.move_dj_time   .set_value("");
+            self.ui.move_dj_impulse.set_value("");
+        }
+        self.ui.move_cap_mode.set_value(match self.kinematics.bhop_cap {
+            BhopCap::None      => "none",
+            BhopCap::Clamp(..) => "clamp",
+            BhopCap::Scale{..} => "scale",
+            BhopCap::Abh  {..} => "abh",
+        });
+        let (cap, factor) = match self.kinematics.bhop_cap {
+            BhopCap::None               => (None     , None        ),
+            BhopCap::Clamp(cap)         => (Some(cap), None        ),
+            BhopCap::Scale{cap, factor} => (Some(cap), Some(factor)),
+            BhopCap::Abh  {boost}       => (None     , Some(boost )),
+        };
+        for (input, value) in &[(&self.ui.move_cap_speed, cap), (&self.ui.move_cap_factor, factor)] {
+            if let Some(value) = value {
+                input.set_disabled(false);
+                input.set_value_as_number(f64::from(*value));
+            } else {
+                input.set_disabled(true);
+                input.set_value("");
+            }
+        }
+        if let Some(crouch_slide) = self.kinematics.crouch_slide {
+            self.ui.move_slide_enable.set_checked(true);
+            self.ui.move_slide_fric  .set_disabled(false);
+            self.ui.move_slide_time  .set_disabled(false);
+            self.ui.move_slide_fric  .set_value_as_number(f64::from(crouch_slide.friction           ));
+            self.ui.move_slide_time  .set_value_as_number(f64::from(crouch_slide.duration_s * 1000.0));
+        } else {
+            self.ui.move_slide_enable.set_checked(false);
+            self.ui.move_slide_fric  .set_disabled(true);
+            self.ui.move_slide_time  .set_disabled(true);
+            self.ui.move_slide_fric  .set_value("");
+            self.ui.move_slide_time  .set_value("");
+        }
+        if let Some(dash) = self.kinematics.dash {
+            self.ui.move_dash_enable.set_checked(true);
+            self.ui.move_dash_speed .set_disabled(false);
+            self.ui.move_dash_up    .set_disabled(false);
+            self.ui.move_dash_time  .set_disabled(false);
+            self.ui.move_dash_speed .set_value_as_number(f64::from(dash.speed              ));
+            self.ui.move_dash_up    .set_value_as_number(f64::from(dash.upward_speed       ));
+            self.ui.move_dash_time  .set_value_as_number(f64::from(dash.cooldown_s * 1000.0));
+        } else {
+            self.ui.move_dash_enable.set_checked(false);
+            self.ui.move_dash_speed .set_disabled(true);
+            self.ui.move_dash_up    .set_disabled(true);
+            self.ui.move_dash_time  .set_disabled(true);
+            self.ui.move_dash_speed .set_value("");
+            self.ui.move_dash_up    .set_value("");
+            self.ui.move_dash_time  .set_value("");
+        }
+        if let Some(wall_jump) = self.kinematics.wall_jump {
+            self.ui.move_wj_enable.set_checked(true);
+            self.ui.move_wj_bounce.set_disabled(false);
+            self.ui.move_wj_speed .set_disabled(false);
+            self.ui.move_wj_up    .set_disabled(false);
+            self.ui.move_wj_time  .set_disabled(false);
+            self.ui.move_wj_bounce.set_value_as_number(f64::from(wall_jump.bounce             ));
+            self.ui.move_wj_speed .set_value_as_number(f64::from(wall_jump.min_speed          ));
+            self.ui.move_wj_up    .set_value_as_number(f64::from(wall_jump.upward_speed       ));
+            self.ui.move_wj_time  .set_value_as_number(f64::from(wall_jump.cooldown_s * 1000.0));
+        } else {
+            self.ui.move_wj_enable.set_checked(false);
+            self.ui.move_wj_bounce.set_disabled(true);
+            self.ui.move_wj_speed .set_disabled(true);
+            self.ui.move_wj_up    .set_disabled(true);
+            self.ui.move_wj_time  .set_disabled(true);
+            self.ui.move_wj_bounce.set_value("");
+            self.ui.move_wj_speed .set_value("");
+            self.ui.move_wj_up    .set_value("");
+            self.ui.move_wj_time  .set_value("");
+        }
+        if let Some(air_control) = self.kinematics.air_control {
+            self.ui.move_ctrl_enabled.set_checked(true);
+            self.ui.move_ctrl_value  .set_disabled(false);
+            self.ui.move_ctrl_value  .set_value_as_number(f64::from(air_control));
+        } else {
+            self.ui.move_ctrl_enabled.set_checked(false);
+            self.ui.move_ctrl_value  .set_disabled(true);
+            self.ui.move_ctrl_value  .set_value("");
+        }
     }
 
     fn validate_movement(&mut self) {
+        // HL2 caps at 1.2x sprint speed, and boosts by a tenth of it while sprinting
+        const DEFAULT_BHOP_CAP   : f32 = 1.2 * 320.0;
+        const DEFAULT_BHOP_FACTOR: f32 = 0.5;
+        const DEFAULT_ABH_BOOST  : f32 = 0.1;
         fn validate(value: &mut f32, default: f32) {
             if !value.is_finite() {
                 *value = default;
@@ -652,12 +910,59 @@ impl Application {
         validate(&mut self.kinematics.friction.friction    , MOVE_VQ3_LIKE.friction.friction    );
         validate(&mut self.kinematics.move_ground.max_speed, MOVE_VQ3_LIKE.move_ground.max_speed);
         validate(&mut self.kinematics.move_ground.accel    , MOVE_VQ3_LIKE.move_ground.accel    );
+        validate(&mut self.kinematics.move_walk.max_speed  , MOVE_VQ3_LIKE.move_walk.max_speed  );
+        validate(&mut self.kinematics.move_walk.accel      , MOVE_VQ3_LIKE.move_walk.accel      );
+        validate(&mut self.kinematics.move_crouch.max_speed, MOVE_VQ3_LIKE.move_crouch.max_speed);
+        validate(&mut self.kinematics.move_crouch.accel    , MOVE_VQ3_LIKE.move_crouch.accel    );
+        validate(&mut self.kinematics.water.movement.max_speed, MOVE_VQ3_LIKE.water.movement.max_speed);
+        validate(&mut self.kinematics.water.movement.accel    , MOVE_VQ3_LIKE.water.movement.accel    );
+        validate(&mut self.kinematics.water.friction          , MOVE_VQ3_LIKE.water.friction          );
+        validate(&mut self.kinematics.ladder.climb_speed      , MOVE_VQ3_LIKE.ladder.climb_speed      );
         validate(&mut self.kinematics.move_air.max_speed   , MOVE_VQ3_LIKE.move_air.max_speed   );
         validate(&mut self.kinematics.move_air.accel       , MOVE_VQ3_LIKE.move_air.accel       );
         if let Some(move_air_turning) = &mut self.kinematics.move_air_turning {
             validate(&mut move_air_turning.max_speed, self.kinematics.move_air.max_speed);
             validate(&mut move_air_turning.accel    , self.kinematics.move_air.accel    );
         }
+        if let Some(move_air_stopping) = &mut self.kinematics.move_air_stopping {
+            validate(&mut move_air_stopping.max_speed, self.kinematics.move_air.max_speed);
+            validate(&mut move_air_stopping.accel    , self.kinematics.move_air.accel    );
+        }
+        if let Some(air_control) = &mut self.kinematics.air_control {
+            validate(air_control, MOVE_CPMA_LIKE.air_control.unwrap_or_default());
+        }
+        if let Some(crouch_slide) = &mut self.kinematics.crouch_slide {
+            let default = MOVE_CPMA_LIKE.crouch_slide.expect("missing default crouch slide");
+            validate(&mut crouch_slide.friction  , default.friction  );
+            validate(&mut crouch_slide.duration_s, default.duration_s);
+        }
+        match &mut self.kinematics.bhop_cap {
+            BhopCap::None => {},
+            BhopCap::Clamp(cap) => validate(cap, DEFAULT_BHOP_CAP),
+            BhopCap::Scale{cap, factor} => {
+                validate(cap   , DEFAULT_BHOP_CAP   );
+                validate(factor, DEFAULT_BHOP_FACTOR);
+            }
+            BhopCap::Abh{boost} => validate(boost, DEFAULT_ABH_BOOST),
+        }
+        if let Some(double_jump) = &mut self.kinematics.double_jump {
+            let default = MOVE_CPMA_LIKE.double_jump.expect("missing default double jump");
+            validate(&mut double_jump.window_s, default.window_s);
+            validate(&mut double_jump.impulse , default.impulse );
+        }
+        if let Some(dash) = &mut self.kinematics.dash {
+            let default = MOVE_WARSOW_LIKE.dash.expect("missing default dash");
+            validate(&mut dash.speed       , default.speed       );
+            validate(&mut dash.upward_speed, default.upward_speed);
+            validate(&mut dash.cooldown_s  , default.cooldown_s  );
+        }
+        if let Some(wall_jump) = &mut self.kinematics.wall_jump {
+            let default = MOVE_WARSOW_LIKE.wall_jump.expect("missing default wall jump");
+            validate(&mut wall_jump.bounce      , default.bounce      );
+            validate(&mut wall_jump.min_speed   , default.min_speed   );
+            validate(&mut wall_jump.upward_speed, default.upward_speed);
+            validate(&mut wall_jump.cooldown_s  , default.cooldown_s  );
+        }
     }
 
     fn update_movement_input(&mut self) {
@@ -667,6 +972,14 @@ impl Application {
         self.kinematics.friction.friction     = self.ui.move_friction    .value_as_number() as f32;
         self.kinematics.move_ground.max_speed = self.ui.move_ground_speed.value_as_number() as f32;
         self.kinematics.move_ground.accel     = self.ui.move_ground_accel.value_as_number() as f32;
+        self.kinematics.move_walk.max_speed   = self.ui.move_walk_speed  .value_as_number() as f32;
+        self.kinematics.move_walk.accel       = self.ui.move_walk_accel  .value_as_number() as f32;
+        self.kinematics.move_crouch.max_speed = self.ui.move_crouch_speed.value_as_number() as f32;
+        self.kinematics.move_crouch.accel     = self.ui.move_crouch_accel.value_as_number() as f32;
+        self.kinematics.water.movement.max_speed = self.ui.move_swim_speed .value_as_number() as f32;
+        self.kinematics.water.movement.accel     = self.ui.move_swim_accel .value_as_number() as f32;
+        self.kinematics.water.friction           = self.ui.move_swim_fric  .value_as_number() as f32;
+        self.kinematics.ladder.climb_speed       = self.ui.move_climb_speed.value_as_number() as f32;
         self.kinematics.move_air.max_speed    = self.ui.move_air_speed   .value_as_number() as f32;
         self.kinematics.move_air.accel        = self.ui.move_air_accel   .value_as_number() as f32;
         if self.ui.move_turn_enabled.checked() {
@@ -681,8 +994,288 @@ impl Application {
             self.ui.move_turn_accel.set_disabled(true);
             self.kinematics.move_air_turning = None;
         }
+        if self.ui.move_stop_enabled.checked() {
+            self.ui.move_stop_speed.set_disabled(false);
+            self.ui.move_stop_accel.set_disabled(false);
+            self.kinematics.move_air_stopping = Some(Movement{
+                max_speed: self.ui.move_stop_speed.value_as_number() as f32,
+                accel    : self.ui.move_stop_accel.value_as_number() as f32,
+            });
+        } else {
+            self.ui.move_stop_speed.set_disabled(true);
+            self.ui.move_stop_accel.set_disabled(true);
+            self.kinematics.move_air_stopping = None;
+        }
+        match self.ui.move_jump_mode.value().as_str() {
+            "hold"            => self.kinematics.jump_mode = JumpMode::Hold,
+            "require-release" => self.kinematics.jump_mode = JumpMode::RequireRelease,
+            "buffer"          => {
+                const DEFAULT_JUMP_BUFFER_S: f32 = 0.1;
+                let window_s = self.ui.move_jump_buffer.value_as_number() as f32 / 1000.0;
+                let window_s = if window_s.is_finite() { window_s } else { DEFAULT_JUMP_BUFFER_S };
+                self.kinematics.jump_mode = JumpMode::Buffer(window_s);
+            }
+            _ => {},
+        }
+        match self.ui.move_land_mode.value().as_str() {
+            "immediate"     => self.kinematics.landing_friction = LandingFriction::Immediate,
+            "friction-free" => {
+                let ticks = self.ui.move_land_ticks.value_as_number();
+                let ticks = if ticks.is_finite() { ticks.clamp(0.0, 50.0) as u32 } else { 0 };
+                self.kinematics.landing_friction = LandingFriction::FrictionFree(ticks);
+            }
+            _ => {},
+        }
+        match self.ui.move_integrator.value().as_str() {
+            "euler"             => self.kinematics.integrator = Integrator::Euler,
+            "half-step-gravity" => self.kinematics.integrator = Integrator::HalfStepGravity,
+            _ => {},
+        }
+        self.kinematics.q3_fidelity = self.ui.move_q3_fidelity.checked();
+        self.kinematics.ramp_jump   = self.ui.move_ramp_jump  .checked();
+        {
+            let cap    = self.ui.move_cap_speed .value_as_number() as f32;
+            let factor = self.ui.move_cap_factor.value_as_number() as f32;
+            match self.ui.move_cap_mode.value().as_str() {
+                "none"  => self.kinematics.bhop_cap = BhopCap::None,
+                "clamp" => self.kinematics.bhop_cap = BhopCap::Clamp(cap),
+                "scale" => self.kinematics.bhop_cap = BhopCap::Scale{cap, factor},
+                "abh"   => self.kinematics.bhop_cap = BhopCap::Abh{boost: factor},
+                _ => {},
+            }
+        }
+        if self.ui.move_dj_enable.checked() {
+            self.ui.move_dj_time   .set_disabled(false);
+            self.ui.move_dj_impulse.set_disabled(false);
+            self.kinematics.double_jump = Some(DoubleJump{
+                window_s: self.ui.move_dj_time   .value_as_number() as f32 / 1000.0,
+                impulse : self.ui.move_dj_impulse.value_as_number() as f32,
+            });
+        } else {
+            self.ui.move_dj_time   .set_disabled(true);
+            self.ui.move_dj_impulse.set_disabled(true);
+            self.kinematics.double_jump = None;
+        }
+        if self.ui.move_slide_enable.checked() {
+            self.ui.move_slide_fric.set_disabled(false);
+            self.ui.move_slide_time.set_disabled(false);
+            self.kinematics.crouch_slide = Some(CrouchSlide{
+                friction  : self.ui.move_slide_fric.value_as_number() as f32,
+                duration_s: self.ui.move_slide_time.value_as_number() as f32 / 1000.0,
+            });
+        } else {
+            self.ui.move_slide_fric.set_disabled(true);
+            self.ui.move_slide_time.set_disabled(true);
+            self.kinematics.crouch_slide = None;
+        }
+        if self.ui.move_dash_enable.checked() {
+            self.ui.move_dash_speed.set_disabled(false);
+            self.ui.move_dash_up   .set_disabled(false);
+            self.ui.move_dash_time .set_disabled(false);
+            self.kinematics.dash = Some(Dash{
+                speed       : self.ui.move_dash_speed.value_as_number() as f32,
+                upward_speed: self.ui.move_dash_up   .value_as_number() as f32,
+                cooldown_s  : self.ui.move_dash_time .value_as_number() as f32 / 1000.0,
+            });
+        } else {
+            self.ui.move_dash_speed.set_disabled(true);
+            self.ui.move_dash_up   .set_disabled(true);
+            self.ui.move_dash_time .set_disabled(true);
+            self.kinematics.dash = None;
+        }
+        if self.ui.move_wj_enable.checked() {
+            self.ui.move_wj_bounce.set_disabled(false);
+            self.ui.move_wj_speed .set_disabled(false);
+            self.ui.move_wj_up    .set_disabled(false);
+            self.ui.move_wj_time  .set_disabled(false);
+            self.kinematics.wall_jump = Some(WallJump{
+                bounce      : self.ui.move_wj_bounce.value_as_number() as f32,
+                min_speed   : self.ui.move_wj_speed .value_as_number() as f32,
+                upward_speed: self.ui.move_wj_up    .value_as_number() as f32,
+                cooldown_s  : self.ui.move_wj_time  .value_as_number() as f32 / 1000.0,
+            });
+        } else {
+            self.ui.move_wj_bounce.set_disabled(true);
+            self.ui.move_wj_speed .set_disabled(true);
+            self.ui.move_wj_up    .set_disabled(true);
+            self.ui.move_wj_time  .set_disabled(true);
+            self.kinematics.wall_jump = None;
+        }
+        if self.ui.move_ctrl_enabled.checked() {
+            self.ui.move_ctrl_value.set_disabled(false);
+            self.kinematics.air_control = Some(self.ui.move_ctrl_value.value_as_number() as f32);
+        } else {
+            self.ui.move_ctrl_value.set_disabled(true);
+            self.kinematics.air_control = None;
+        }
         self.validate_movement();
         self.update_movement_display();
+        self.save_movement_settings();
+    }
+
+    fn set_movement(&mut self, kinematics: Kinematics) {
+        self.kinematics = kinematics;
+        self.validate_movement();
+        self.update_movement_display();
+        self.save_movement_settings();
+    }
+
+    fn update_preset_display(&mut self) {
+        self.ui.preset_list.set_inner_html("");
+        for preset in &self.movement_presets {
+            let button = self.ui.document.create_element("button")
+                .expect("failed to create preset button");
+            button.set_text_content(Some(preset.name.as_str()));
+            button.set_attribute("data-preset", preset.name.as_str())
+                .expect("failed to set preset button name");
+            self.ui.preset_list.append_child(&button)
+                .expect("failed to add preset button");
+        }
+    }
+
+    fn preset_name(&self) -> Option<String> {
+        let name = self.ui.preset_name.value().trim().to_string();
+        if name.is_empty() { None } else { Some(name) }
+    }
+
+    fn add_preset(&mut self, preset: MovementPreset) {
+        self.movement_presets.retain(|other| other.name != preset.name);
+        self.movement_presets.push(preset);
+        self.update_preset_display();
+        self.save_movement_settings();
+    }
+
+    fn select_preset(&mut self, name: &str) {
+        let kinematics = self.movement_presets.iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| preset.kinematics.clone());
+        if let Some(kinematics) = kinematics {
+            self.ui.preset_name.set_value(name);
+            self.set_movement(kinematics);
+        }
+    }
+
+    fn save_preset(&mut self) {
+        if let Some(name) = self.preset_name() {
+            self.add_preset(MovementPreset{name, kinematics: self.kinematics.clone()});
+        } else {
+            warn("cannot save preset; no name given");
+        }
+    }
+
+    fn delete_preset(&mut self) {
+        if let Some(name) = self.preset_name() {
+            self.movement_presets.retain(|preset| preset.name != name);
+            self.update_preset_display();
+            self.save_movement_settings();
+        }
+    }
+
+    fn export_preset(&mut self) {
+        let preset = MovementPreset{
+            name: self.preset_name().unwrap_or_else(|| "Custom".to_string()),
+            kinematics: self.kinematics.clone(),
+        };
+        match preset.to_json() {
+            Ok(data) => self.ui.preset_json.set_value(data.as_str()),
+            Err(_) => error("failed to export preset"),
+        }
+    }
+
+    fn import_preset(&mut self) {
+        match MovementPreset::from_json(self.ui.preset_json.value().as_str()) {
+            Ok(preset) => {
+                self.ui.preset_name.set_value(preset.name.as_str());
+                self.set_movement(preset.kinematics);
+                self.add_preset(MovementPreset{name: preset.name, kinematics: self.kinematics.clone()});
+            }
+            Err(_) => warn("cannot import preset; invalid JSON"),
+        }
+    }
+
+    fn import_cfg(&mut self) {
+        let dialect = match self.ui.cfg_dialect.value().as_str() {
+            "quake3"     => Dialect::Quake3,
+            "quakeworld" => Dialect::QuakeWorld,
+            "source"     => Dialect::Source,
+            _ => return,
+        };
+        let mut kinematics = self.kinematics.clone();
+        let warnings = apply_cfg(dialect, self.ui.cfg_text.value().as_str(), &mut kinematics);
+        self.ui.cfg_warnings.set_text_content(Some(warnings.join("\n").as_str()));
+        self.set_movement(kinematics);
+    }
+
+    fn update_tick_rate_display(&mut self) {
+        self.ui.tick_rate.set_value(match self.sim_settings.tick_rate {
+            TickRate::Tick64  => "64",
+            TickRate::Tick66  => "66",
+            TickRate::Tick100 => "100",
+            TickRate::Tick128 => "128",
+            TickRate::Fps125  => "fps-125",
+            TickRate::Fps250  => "fps-250",
+            TickRate::Fps333  => "fps-333",
+        });
+    }
+
+    fn update_tick_rate_input(&mut self) {
+        match self.ui.tick_rate.value().as_str() {
+            "64"      => self.sim_settings.tick_rate = TickRate::Tick64,
+            "66"      => self.sim_settings.tick_rate = TickRate::Tick66,
+            "100"     => self.sim_settings.tick_rate = TickRate::Tick100,
+            "128"     => self.sim_settings.tick_rate = TickRate::Tick128,
+            "fps-125" => self.sim_settings.tick_rate = TickRate::Fps125,
+            "fps-250" => self.sim_settings.tick_rate = TickRate::Fps250,
+            "fps-333" => self.sim_settings.tick_rate = TickRate::Fps333,
+            _ => {},
+        }
+        self.update_tick_rate_display();
+        self.save_sim_settings();
+    }
+
+    fn update_weapon_input(&mut self) {
+        self.weapon = match self.ui.weapon.value().as_str() {
+            "rocket" => Some(WEAPON_ROCKET),
+            "plasma" => Some(WEAPON_PLASMA),
+            _ => None,
+        };
+        self.arsenal.reset();
+    }
+
+    fn restart_ghost(&mut self) {
+        if let Some(ghost) = &mut self.ghost {
+            ghost.restart(self.player_state.clone());
+        }
+    }
+
+    fn update_compare_input(&mut self) {
+        let kinematics = match self.ui.compare.value().as_str() {
+            "snapshot"    => Some(self.kinematics.clone()),
+            "vq3-like"    => Some(MOVE_VQ3_LIKE),
+            "qw-like"     => Some(MOVE_QW_LIKE),
+            "hybrid"      => Some(MOVE_HYBRID),
+            "cpma-like"   => Some(MOVE_CPMA_LIKE),
+            "source-like" => Some(MOVE_SOURCE_LIKE),
+            "warsow-like" => Some(MOVE_WARSOW_LIKE),
+            _ => None,
+        };
+        match (kinematics, &mut self.ghost) {
+            (Some(kinematics), Some(ghost)) => {
+                // replay the recording, up to MAX_RECORDING_S of this run, under the new kinematics
+                for input in ghost.set_kinematics(kinematics) {
+                    ghost.sim(input, &*self.map);
+                    self.map.interact(&mut ghost.player_state);
+                }
+            }
+            (Some(kinematics), None) => {
+                self.ghost = Some(Ghost::new(kinematics, self.player_state.clone()));
+                show(self.ui.speed_delta.dyn_ref::<Element>().unwrap());
+            }
+            (None, _) => {
+                self.ghost = None;
+                hide(self.ui.speed_delta.dyn_ref::<Element>().unwrap());
+            }
+        }
     }
 
     fn update_bot_display(&mut self) {
@@ -702,13 +1295,77 @@ impl Application {
             self.ui.bot_hop .set_checked(self.auto_hop);
             self.ui.bot_move.set_checked(self.auto_move);
             self.ui.bot_turn.set_checked(self.auto_turn);
+            self.ui.bot_assist.set_checked(self.assist.is_some());
         } else {
             self.ui.bot_hop .set_checked(false);
             self.ui.bot_move.set_checked(false);
             self.ui.bot_turn.set_checked(false);
+            self.ui.bot_assist.set_checked(false);
             self.ui.bot_hop .set_disabled(true);
             self.ui.bot_move.set_disabled(true);
             self.ui.bot_turn.set_disabled(true);
+            self.ui.bot_assist.set_disabled(true);
+        }
+        self.update_bot_start_display();
+        self.update_bot_skill_display();
+    }
+
+    fn update_bot_start_display(&mut self) {
+        let start = self.strafe_bot.as_ref().map_or_else(StartRoutine::default, StrafeBot::start);
+        // an edited angle or position no longer matches any of the starts listed
+        self.ui.bot_start.set_value(BOT_STARTS.iter()
+            .find(|(_, other)| *other == start)
+            .map_or("custom", |(name, _)| name));
+        self.ui.bot_start_angle.set_value_as_number(f64::from(start.angle.0));
+        self.ui.bot_start_x    .set_value_as_number(f64::from(start.start_x));
+        let is_disabled = self.strafe_bot.is_none();
+        self.ui.bot_start      .set_disabled(is_disabled);
+        self.ui.bot_start_angle.set_disabled(is_disabled);
+        self.ui.bot_start_x    .set_disabled(is_disabled);
+    }
+
+    fn bot_skill_input(&self) -> Option<Skill> {
+        let level = self.ui.bot_skill.value_as_number();
+        let seed  = self.ui.bot_seed .value_as_number();
+        if !level.is_finite() || !seed.is_finite() {
+            return None;
+        }
+        let skill = Skill::at_level((level / 100.0) as f32, seed.max(0.0) as u64);
+        Some(Skill{
+            aim_error       : if self.ui.bot_flaw_aim .checked() { skill.aim_error        } else { Deg(0.0) },
+            key_latency_s   : if self.ui.bot_flaw_keys.checked() { skill.key_latency_s    } else { 0.0 },
+            jump_timing_s   : if self.ui.bot_flaw_jump.checked() { skill.jump_timing_s    } else { 0.0 },
+            turn_rate_spread: if self.ui.bot_flaw_turn.checked() { skill.turn_rate_spread } else { 0.0 },
+            ..skill
+        })
+    }
+
+    // the inputs hold the level a skill came from, so they're only reset
+    // for a bot made elsewhere, e.g. by the tutorial, which plays perfectly
+    fn update_bot_skill_display(&mut self) {
+        if !self.ui.bot_seed.value_as_number().is_finite() {
+            self.ui.bot_seed.set_value_as_number(0.0);
+        }
+        let skill = self.strafe_bot.as_ref().map_or_else(Skill::default, StrafeBot::skill);
+        if skill.is_perfect() && !self.bot_skill_input().is_some_and(|input| input.is_perfect()) {
+            self.ui.bot_skill.set_value_as_number(100.0);
+        }
+        let is_disabled = self.strafe_bot.is_none();
+        self.ui.bot_skill    .set_disabled(is_disabled);
+        self.ui.bot_seed     .set_disabled(is_disabled);
+        self.ui.bot_flaw_aim .set_disabled(is_disabled);
+        self.ui.bot_flaw_keys.set_disabled(is_disabled);
+        self.ui.bot_flaw_jump.set_disabled(is_disabled);
+        self.ui.bot_flaw_turn.set_disabled(is_disabled);
+    }
+
+    // a start picked from the list resets its angle and position, so those
+    // can be tuned from there
+    fn select_bot_start(&mut self) {
+        let value = self.ui.bot_start.value();
+        if let Some((_, start)) = BOT_STARTS.iter().find(|(name, _)| *name == value) {
+            self.ui.bot_start_angle.set_value_as_number(f64::from(start.angle.0));
+            self.ui.bot_start_x    .set_value_as_number(f64::from(start.start_x));
         }
     }
 
@@ -719,7 +1376,7 @@ impl Application {
             } else {
                 *bot = Some(StrafeBot::new(config));
             }
-        };
+        }
         match self.ui.bot_mode.value().as_str() {
             "player-keys"    => update_config(&mut self.strafe_bot, StrafeConfig::PLAYER_KEYS),
             "standard"       => update_config(&mut self.strafe_bot, StrafeConfig::STANDARD),
@@ -732,11 +1389,34 @@ impl Application {
             "disabled"       => { self.strafe_bot = None },
             _ => {},
         }
-        if let Some(bot) = &self.strafe_bot {
+        let skill = self.bot_skill_input();
+        if let Some(bot) = &mut self.strafe_bot {
+            let value = self.ui.bot_start.value();
+            let kind = BOT_STARTS.iter()
+                .find(|(name, _)| *name == value)
+                .map_or(bot.start().kind, |(_, start)| start.kind);
+            let angle   = self.ui.bot_start_angle.value_as_number();
+            let start_x = self.ui.bot_start_x    .value_as_number();
+            if angle.is_finite() && start_x.is_finite() {
+                bot.set_start(StartRoutine{
+                    kind,
+                    angle: Deg(angle.clamp(0.0, 360.0) as f32),
+                    start_x: start_x.clamp(-960.0, 960.0) as f32,
+                });
+            }
+            if let Some(skill) = skill {
+                bot.set_skill(skill);
+            }
             self.auto_hop  = self.ui.bot_hop .checked();
             self.auto_turn = self.ui.bot_turn.checked();
             self.ui.bot_hop .set_disabled(false);
             self.ui.bot_turn.set_disabled(false);
+            self.ui.bot_assist.set_disabled(false);
+            if !self.ui.bot_assist.checked() {
+                self.assist = None;
+            } else if self.assist.is_none() {
+                self.assist = Some(Assist::new());
+            }
             if bot.config == StrafeConfig::PLAYER_KEYS {
                 self.auto_move = false;
                 self.ui.bot_move.set_disabled(true);
@@ -749,11 +1429,15 @@ impl Application {
             self.auto_hop  = false;
             self.auto_move = false;
             self.auto_turn = false;
+            self.assist    = None;
             self.ui.bot_hop .set_disabled(true);
             self.ui.bot_move.set_disabled(true);
             self.ui.bot_turn.set_disabled(true);
+            self.ui.bot_assist.set_disabled(true);
             hide(&self.ui.keys);
         }
+        self.update_bot_start_display();
+        self.update_bot_skill_display();
     }
 
     fn setup_events(app: Rc<RefCell<Self>>) {
@@ -967,7 +1651,11 @@ impl Application {
             KeyCode::KeyS,
             KeyCode::KeyD,
             KeyCode::KeyF,
+            KeyCode::KeyC,
+            KeyCode::KeyE,
+            KeyCode::Shift,
             KeyCode::Space,
+            KeyCode::MouseLeft,
         ].iter().for_each(|&target| {
             let callback = {
                 let app = app.clone();
@@ -1014,14 +1702,16 @@ impl Application {
         let gen_move_preset_cb = |kinematics: Kinematics| {
             let app = app.clone();
             Closure::wrap(Box::new(move || {
-                app.borrow_mut().kinematics = kinematics.clone();
-                app.borrow_mut().update_movement_display();
+                app.borrow_mut().set_movement(kinematics.clone());
             }) as Box<dyn FnMut()>)
         };
 
         let move_vq3_like_cb = gen_move_preset_cb(MOVE_VQ3_LIKE);
         let move_qw_like_cb = gen_move_preset_cb(MOVE_QW_LIKE);
         let move_hybrid_cb = gen_move_preset_cb(MOVE_HYBRID);
+        let move_cpma_like_cb = gen_move_preset_cb(MOVE_CPMA_LIKE);
+        let move_source_like_cb = gen_move_preset_cb(MOVE_SOURCE_LIKE);
+        let move_warsow_like_cb = gen_move_preset_cb(MOVE_WARSOW_LIKE);
 
         app.borrow().ui.move_vq3_like.add_event_listener_with_callback("click",
             move_vq3_like_cb.as_ref().dyn_ref().unwrap())
@@ -1035,6 +1725,128 @@ impl Application {
             move_hybrid_cb.as_ref().dyn_ref().unwrap())
             .expect("failed to add move_hybrid click listener");
 
+        app.borrow().ui.move_cpma_like.add_event_listener_with_callback("click",
+            move_cpma_like_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_cpma_like click listener");
+
+        app.borrow().ui.move_source_like.add_event_listener_with_callback("click",
+            move_source_like_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_source_like click listener");
+
+        app.borrow().ui.move_warsow_like.add_event_listener_with_callback("click",
+            move_warsow_like_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_warsow_like click listener");
+
+        let preset_list_cb = {
+            let app = app.clone();
+            Closure::wrap(Box::new(move |event: MouseEvent| {
+                let name = event.target()
+                    .and_then(|target| target.dyn_into::<Element>().ok())
+                    .and_then(|element| element.get_attribute("data-preset"));
+                if let Some(name) = name {
+                    app.borrow_mut().select_preset(name.as_str());
+                }
+            }) as Box<dyn FnMut(_)>)
+        };
+
+        app.borrow().ui.preset_list.add_event_listener_with_callback("click",
+            preset_list_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add preset_list click listener");
+
+        let gen_action_cb = |f: fn(&mut Application)| {
+            let app = app.clone();
+            Closure::wrap(Box::new(move || {
+                f(&mut app.borrow_mut());
+            }) as Box<dyn FnMut()>)
+        };
+
+        let preset_save_cb = gen_action_cb(Application::save_preset);
+        let preset_delete_cb = gen_action_cb(Application::delete_preset);
+        let preset_export_cb = gen_action_cb(Application::export_preset);
+        let preset_import_cb = gen_action_cb(Application::import_preset);
+
+        app.borrow().ui.preset_save.add_event_listener_with_callback("click",
+            preset_save_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add preset_save click listener");
+
+        app.borrow().ui.preset_delete.add_event_listener_with_callback("click",
+            preset_delete_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add preset_delete click listener");
+
+        app.borrow().ui.preset_export.add_event_listener_with_callback("click",
+            preset_export_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add preset_export click listener");
+
+        app.borrow().ui.preset_import.add_event_listener_with_callback("click",
+            preset_import_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add preset_import click listener");
+
+        let cfg_import_cb = gen_action_cb(Application::import_cfg);
+
+        app.borrow().ui.cfg_import.add_event_listener_with_callback("click",
+            cfg_import_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add cfg_import click listener");
+
+        let cfg_file_cb = {
+            let app = app.clone();
+            Closure::wrap(Box::new(move || {
+                let file = app.borrow().ui.cfg_file.files()
+                    .and_then(|files| files.get(0));
+                if let Some(file) = file {
+                    let reader = FileReader::new()
+                        .expect("failed to create FileReader");
+                    let load_cb = {
+                        let app = app.clone();
+                        let reader = reader.clone();
+                        Closure::once_into_js(move || {
+                            if let Some(cfg) = reader.result().ok().and_then(|result| result.as_string()) {
+                                app.borrow().ui.cfg_text.set_value(cfg.as_str());
+                                app.borrow_mut().import_cfg();
+                            } else {
+                                error("failed to read config file");
+                            }
+                        })
+                    };
+                    reader.set_onload(Some(load_cb.unchecked_ref()));
+                    if reader.read_as_text(&file).is_err() {
+                        error("failed to read config file");
+                    }
+                }
+            }) as Box<dyn FnMut()>)
+        };
+
+        app.borrow().ui.cfg_file.add_event_listener_with_callback("change",
+            cfg_file_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add cfg_file change listener");
+
+        let update_tick_rate_cb = {
+            let app = app.clone();
+            Closure::wrap(Box::new(move || {
+                app.borrow_mut().update_tick_rate_input();
+            }) as Box<dyn FnMut()>)
+        };
+
+        app.borrow().ui.tick_rate.add_event_listener_with_callback("change",
+            update_tick_rate_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add tick_rate change listener");
+
+        let update_compare_cb = gen_action_cb(Application::update_compare_input);
+
+        app.borrow().ui.compare.add_event_listener_with_callback("change",
+            update_compare_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add compare change listener");
+
+        let update_weapon_cb = {
+            let app = app.clone();
+            Closure::wrap(Box::new(move || {
+                app.borrow_mut().update_weapon_input();
+            }) as Box<dyn FnMut()>)
+        };
+
+        app.borrow().ui.weapon.add_event_listener_with_callback("change",
+            update_weapon_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add weapon change listener");
+
         let update_movement_cb = {
             let app = app.clone();
             Closure::wrap(Box::new(move || {
@@ -1049,17 +1861,67 @@ impl Application {
             &app.borrow().ui.move_friction    ,
             &app.borrow().ui.move_ground_speed,
             &app.borrow().ui.move_ground_accel,
+            &app.borrow().ui.move_walk_speed  ,
+            &app.borrow().ui.move_walk_accel  ,
+            &app.borrow().ui.move_crouch_speed,
+            &app.borrow().ui.move_crouch_accel,
+            &app.borrow().ui.move_swim_speed  ,
+            &app.borrow().ui.move_swim_accel  ,
+            &app.borrow().ui.move_swim_fric   ,
+            &app.borrow().ui.move_climb_speed ,
             &app.borrow().ui.move_air_speed   ,
             &app.borrow().ui.move_air_accel   ,
             &app.borrow().ui.move_turn_enabled,
             &app.borrow().ui.move_turn_speed  ,
             &app.borrow().ui.move_turn_accel  ,
+            &app.borrow().ui.move_stop_enabled,
+            &app.borrow().ui.move_stop_speed  ,
+            &app.borrow().ui.move_stop_accel  ,
+            &app.borrow().ui.move_ctrl_enabled,
+            &app.borrow().ui.move_ctrl_value  ,
+            &app.borrow().ui.move_q3_fidelity ,
+            &app.borrow().ui.move_ramp_jump   ,
+            &app.borrow().ui.move_dj_enable   ,
+            &app.borrow().ui.move_dj_time     ,
+            &app.borrow().ui.move_dj_impulse  ,
+            &app.borrow().ui.move_cap_speed   ,
+            &app.borrow().ui.move_cap_factor  ,
+            &app.borrow().ui.move_jump_buffer ,
+            &app.borrow().ui.move_land_ticks  ,
+            &app.borrow().ui.move_slide_enable,
+            &app.borrow().ui.move_slide_fric  ,
+            &app.borrow().ui.move_slide_time  ,
+            &app.borrow().ui.move_dash_enable ,
+            &app.borrow().ui.move_dash_speed  ,
+            &app.borrow().ui.move_dash_up     ,
+            &app.borrow().ui.move_dash_time   ,
+            &app.borrow().ui.move_wj_enable   ,
+            &app.borrow().ui.move_wj_bounce   ,
+            &app.borrow().ui.move_wj_speed    ,
+            &app.borrow().ui.move_wj_up       ,
+            &app.borrow().ui.move_wj_time     ,
         ].iter().for_each(|element| {
             element.add_event_listener_with_callback("change",
                 update_movement_cb.as_ref().dyn_ref().unwrap())
                 .expect("failed to add movement value change listener");
         });
 
+        app.borrow().ui.move_integrator.add_event_listener_with_callback("change",
+            update_movement_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_integrator change listener");
+
+        app.borrow().ui.move_jump_mode.add_event_listener_with_callback("change",
+            update_movement_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_jump_mode change listener");
+
+        app.borrow().ui.move_land_mode.add_event_listener_with_callback("change",
+            update_movement_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_land_mode change listener");
+
+        app.borrow().ui.move_cap_mode.add_event_listener_with_callback("change",
+            update_movement_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add move_cap_mode change listener");
+
         let update_bot_cb = {
             let app = app.clone();
             Closure::wrap(Box::new(move || {
@@ -1067,6 +1929,18 @@ impl Application {
             }) as Box<dyn FnMut()>)
         };
 
+        // ahead of update_bot_cb, which hears it bubble up through menu_bot
+        let select_bot_start_cb = {
+            let app = app.clone();
+            Closure::wrap(Box::new(move || {
+                app.borrow_mut().select_bot_start();
+            }) as Box<dyn FnMut()>)
+        };
+
+        app.borrow().ui.bot_start.add_event_listener_with_callback("change",
+            select_bot_start_cb.as_ref().dyn_ref().unwrap())
+            .expect("failed to add bot_start change listener");
+
         [
             &app.borrow().ui.menu_bot,
             &app.borrow().ui.bot_hop,
@@ -1098,13 +1972,27 @@ impl Application {
         move_vq3_like_cb.forget();
         move_qw_like_cb.forget();
         move_hybrid_cb.forget();
+        move_cpma_like_cb.forget();
+        move_source_like_cb.forget();
+        move_warsow_like_cb.forget();
+        preset_list_cb.forget();
+        preset_save_cb.forget();
+        preset_delete_cb.forget();
+        preset_export_cb.forget();
+        preset_import_cb.forget();
+        cfg_import_cb.forget();
+        cfg_file_cb.forget();
+        update_tick_rate_cb.forget();
+        update_compare_cb.forget();
+        update_weapon_cb.forget();
         update_movement_cb.forget();
+        select_bot_start_cb.forget();
         update_bot_cb.forget();
     }
 
-    fn override_hopping(&self) -> bool { self.strafe_bot.as_ref().map_or(false, |bot| self.auto_hop  || bot.is_setting_up()) }
-    fn override_moving (&self) -> bool { self.strafe_bot.as_ref().map_or(false, |bot| self.auto_move || bot.is_setting_up()) }
-    fn override_turning(&self) -> bool { self.strafe_bot.as_ref().map_or(false, |bot| self.auto_turn || bot.is_setting_up()) }
+    fn override_hopping(&self) -> bool { self.strafe_bot.as_ref().is_some_and(|bot| self.auto_hop  || bot.is_setting_up()) }
+    fn override_moving (&self) -> bool { self.strafe_bot.as_ref().is_some_and(|bot| self.auto_move || bot.is_setting_up()) }
+    fn override_turning(&self) -> bool { self.strafe_bot.as_ref().is_some_and(|bot| self.auto_turn || bot.is_setting_up()) }
 
     fn tick_sim(&mut self, dt: f32) {
         let u = dt / self.tick_remainder_s;
@@ -1114,14 +2002,16 @@ impl Application {
         self.input_rotation.1 -= pitch;
         self.player_state.add_rotation(yaw, pitch);
 
-        let is_jumping = self.key_state.space;
-        let is_turning = self.key_state.is_side_strafe();
-
-        let wish_dir = self.player_state.wish_dir(self.key_state, Rad::zero(), Rad::zero());
-        self.player_state.sim_kinematics(&self.kinematics, dt, wish_dir, is_jumping, is_turning);
+        self.player_state.sim_kinematics(&self.kinematics, dt, self.key_state, &*self.map);
+        self.arsenal.sim(self.weapon, &mut self.player_state, dt, self.key_state, &*self.map);
 
         self.map.interact(&mut self.player_state);
 
+        if let Some(ghost) = &mut self.ghost {
+            ghost.sim(TickInput{dt, keys: self.key_state, dir: self.player_state.dir}, &*self.map);
+            self.map.interact(&mut ghost.player_state);
+        }
+
         self.tick_remainder_s -= dt;
     }
 
@@ -1197,6 +2087,16 @@ impl Application {
             self.key_state.key_a = self.bot_key_state.key_a;
             self.key_state.key_s = self.bot_key_state.key_s;
             self.key_state.key_d = self.bot_key_state.key_d;
+        } else if let (Some(assist), Some(bot)) = (&self.assist, &self.strafe_bot) {
+            let wish_dir = self.player_state.wish_dir(self.key_state, self.input_rotation.0, Rad::zero()).xy();
+            let bot_wish_dir = self.player_state.wish_dir(self.bot_key_state,
+                bot.view_yaw() - self.player_state.dir.0, Rad::zero()).xy();
+            if assist.is_off(wish_dir, bot_wish_dir) {
+                self.key_state.key_w = self.bot_key_state.key_w;
+                self.key_state.key_a = self.bot_key_state.key_a;
+                self.key_state.key_s = self.bot_key_state.key_s;
+                self.key_state.key_d = self.bot_key_state.key_d;
+            }
         }
 
         if self.override_hopping() {
@@ -1218,7 +2118,7 @@ impl Application {
         self.gl.gl().clear(WebGlRenderingContext::COLOR_BUFFER_BIT | WebGlRenderingContext::DEPTH_BUFFER_BIT);
 
         const MAX_FRAME_DURATION_S: f32 = 0.2;
-        const TICK_DURATION_S: f32 = 0.01;
+        let tick_duration_s = self.sim_settings.tick_rate.duration_s();
 
         let current_frame_us = (1_000.0 * self.ui.window.performance().unwrap().now()) as u32;
         let frame_duration_s = (current_frame_us - self.last_frame_us) as f32 / 1_000_000.0;
@@ -1230,8 +2130,8 @@ impl Application {
             self.tick_remainder_s = MAX_FRAME_DURATION_S;
         }
 
-        while self.tick_remainder_s > TICK_DURATION_S {
-            self.tick_sim(TICK_DURATION_S);
+        while self.tick_remainder_s > tick_duration_s {
+            self.tick_sim(tick_duration_s);
         }
 
         let view_matrix = self.player_state.view_matrix(
@@ -1247,11 +2147,58 @@ impl Application {
             self.gl.gl().enable(WebGlRenderingContext::CULL_FACE);
             self.gl.gl().cull_face(WebGlRenderingContext::BACK);
 
+            // the map doesn't set this, so it mustn't be left over from the last frame's ghost
+            self.main_program.use_program(self.gl.gl());
+            self.main_program.set_uniform(self.gl.gl(), "ghost", &ConstantValue::Float(0.0));
+
             self.map.draw(self.gl.gl(),
                 &self.main_program,
                 &view_matrix,
                 &projection_matrix);
 
+            if let Some(ghost) = &self.ghost {
+                let pos = ghost.player_state.pos + ghost.player_state.vel * self.tick_remainder_s;
+                let height = ghost.player_state.hull().height;
+                let transform = Matrix4::from_translation(pos.to_vec())
+                    * Matrix4::from_nonuniform_scale(1.0, 1.0, height / PLAYER_HEIGHT);
+
+                self.gl.gl().enable(WebGlRenderingContext::BLEND);
+                self.gl.gl().blend_func(
+                    WebGlRenderingContext::SRC_ALPHA,
+                    WebGlRenderingContext::ONE_MINUS_SRC_ALPHA);
+
+                draw_pass(self.gl.gl(), &self.main_program, &[
+                    ("V"        , Constant::Uniform(ConstantValue::Matrix4(view_matrix))),
+                    ("P"        , Constant::Uniform(ConstantValue::Matrix4(projection_matrix))),
+                    ("fog_color", Constant::Uniform(ConstantValue::Color(self.map.atmosphere_color()))),
+                    ("M_group"  , Constant::Uniform(ConstantValue::Matrix4(Matrix4::identity()))),
+                    ("ghost"    , Constant::Uniform(ConstantValue::Float(1.0))),
+                ], vec![
+                    (&[("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(transform)))][..],
+                        self.ghost_mesh.clone(), None),
+                ]);
+
+                self.gl.gl().disable(WebGlRenderingContext::BLEND);
+            }
+
+            let projectile_constants = self.arsenal.projectiles.iter()
+                .map(|projectile| {
+                    let pos = projectile.pos + projectile.vel * self.tick_remainder_s;
+                    [("M_instance", Constant::VertexAttrib(ConstantValue::Matrix4(
+                        Matrix4::from_translation(pos.to_vec()))))]
+                })
+                .collect::<Vec<_>>();
+            draw_pass(self.gl.gl(), &self.main_program, &[
+                ("V"        , Constant::Uniform(ConstantValue::Matrix4(view_matrix))),
+                ("P"        , Constant::Uniform(ConstantValue::Matrix4(projection_matrix))),
+                ("fog_color", Constant::Uniform(ConstantValue::Color(self.map.atmosphere_color()))),
+                ("M_group"  , Constant::Uniform(ConstantValue::Matrix4(Matrix4::identity()))),
+                ("ghost"    , Constant::Uniform(ConstantValue::Float(0.0))),
+            ], projectile_constants.iter()
+                .map(|constants| -> (&[_], _, _) {
+                    (constants, self.projectile_mesh.clone(), None)
+                }));
+
             if let Some(warp_effect) = &mut self.warp_effect {
                 if let AnyGlContext::Gl2(gl) = &self.gl {
                     warp_effect.draw(gl, &view_matrix, &projection_matrix, self.player_state.vel, frame_duration_s);
@@ -1261,10 +2208,10 @@ impl Application {
             self.gl.gl().disable(WebGlRenderingContext::DEPTH_TEST);
         }
 
-        let is_jumping = self.key_state.space;
-        let is_grounded = self.player_state.is_grounded() && !is_jumping;
-        let is_turning = self.key_state.is_side_strafe();
-        let max_speed = self.kinematics.effective_movement(is_grounded, is_turning).max_speed;
+        let speed = self.player_state.vel.xy().magnitude();
+        let tick_duration_s = self.sim_settings.tick_rate.duration_s();
+        let ground = self.player_state.ground_state(self.key_state);
+        let strafe = Strafe::simulate(&self.kinematics, self.player_state.vel, ground, self.key_state, tick_duration_s);
 
         {
             let fovx = Rad::atan(self.perspective.aspect * (self.perspective.fovy / 2.0).tan()) * 2.0;
@@ -1273,9 +2220,8 @@ impl Application {
                 self.input_rotation.0,
                 self.input_rotation.1).xy();
             let velocity_xy = self.player_state.vel.xy();
-            let speed = velocity_xy.magnitude();
             let move_dir = if speed > 0.0001 { velocity_xy / speed } else { Vector2::zero() };
-            let warp_factor = speed / max_speed;
+            let warp_factor = speed / strafe.max_speed;
 
             self.gl.gl().enable(WebGlRenderingContext::BLEND);
             self.gl.gl().blend_func(
@@ -1287,6 +2233,9 @@ impl Application {
                 ("wish_dir"    , Constant::Uniform(ConstantValue::Vector2(wish_dir   ))),
                 ("move_dir"    , Constant::Uniform(ConstantValue::Vector2(move_dir   ))),
                 ("warp_factor" , Constant::Uniform(ConstantValue::Float  (warp_factor))),
+                ("cos_min"     , Constant::Uniform(ConstantValue::Float  (strafe.min_angle    .cos()))),
+                ("cos_optimal" , Constant::Uniform(ConstantValue::Float  (strafe.optimal_angle.cos()))),
+                ("cos_max"     , Constant::Uniform(ConstantValue::Float  (strafe.max_angle    .cos()))),
             ], vec![
                 (&[], self.hud_mesh.clone(), None),
             ]);
@@ -1295,11 +2244,14 @@ impl Application {
         }
 
         if let Some(strafe_bot) = &mut self.strafe_bot {
+            strafe_bot.avoid_obstacles = self.map_option == MapOption::Freestyle;
             let (keys, theta, phi) = strafe_bot.sim(frame_duration_s,
-                &self.player_state, self.input_key_state, max_speed,
-                self.input_rotation.0, self.input_rotation.1);
+                &self.player_state, self.input_key_state,
+                &self.kinematics, tick_duration_s, self.tick_remainder_s,
+                self.input_rotation.0, self.input_rotation.1, &*self.map);
             self.bot_key_history = self.bot_key_state;
             self.bot_key_state   = keys;
+            let view_yaw = strafe_bot.view_yaw();
 
             let pressed  = self.bot_key_state.pressed (self.bot_key_history);
             let released = self.bot_key_state.released(self.bot_key_history);
@@ -1319,9 +2271,25 @@ impl Application {
             if self.override_turning() {
                 self.input_rotation.0 += theta;
                 self.input_rotation.1 += phi;
+            } else if let Some(assist) = &mut self.assist {
+                // the player's error is recorded before this frame's correction
+                let error = view_yaw - (self.player_state.dir.0 + self.input_rotation.0);
+                assist.update(frame_duration_s, error);
+                // pulled back to the threshold, and never past the bot's view
+                let correction = assist.correction(error);
+                let max_correction = error.normalize_signed().0.abs();
+                self.input_rotation.0 += Rad(correction.0.signum() * correction.0.abs().min(max_correction));
             }
         }
 
+        if let Some(assist) = &self.assist {
+            self.ui.assist.dyn_ref::<web_sys::Node>().unwrap()
+                .set_text_content(Some(format!("Assist \u{b1}{:.1}\u{b0}", Deg::from(assist.threshold()).0).as_str()));
+            show(self.ui.assist.dyn_ref::<Element>().unwrap());
+        } else {
+            hide(self.ui.assist.dyn_ref::<Element>().unwrap());
+        }
+
         {
             let ground_speed = self.player_state.vel.xy().magnitude();
             self.update_tutorial(frame_duration_s, ground_speed, keys_pressed.key_f);
@@ -1338,6 +2306,32 @@ impl Application {
                 .set_text_content(Some(format!("{:.1}MPH", speed_mph).as_str()));
             self.ui.speed_kph.dyn_ref::<web_sys::Node>().unwrap()
                 .set_text_content(Some(format!("{:.1}KPH", speed_kph).as_str()));
+
+            if let Some(ghost) = &self.ghost {
+                let speed_delta = ghost.player_state.vel.xy().magnitude() - speed_ups;
+                self.ui.speed_delta.dyn_ref::<web_sys::Node>().unwrap()
+                    .set_text_content(Some(format!("Ghost {:+.1}UPS", speed_delta).as_str()));
+            }
+        }
+
+        if self.player_state.is_double_jump_ready() {
+            show(self.ui.double_jump.dyn_ref::<Element>().unwrap());
+        } else {
+            hide(self.ui.double_jump.dyn_ref::<Element>().unwrap());
+        }
+
+        if let Some(hop_ticks) = self.player_state.hop_ticks {
+            let text = match (hop_ticks, self.player_state.perfect_hop_streak) {
+                (0, 1)      => "Perfect Hop".to_string(),
+                (0, streak) => format!("Perfect Hop \u{d7}{}", streak),
+                (1, _)      => "Hop +1 tick".to_string(),
+                (ticks, _)  => format!("Hop +{} ticks", ticks),
+            };
+            self.ui.hop_ticks.dyn_ref::<web_sys::Node>().unwrap()
+                .set_text_content(Some(text.as_str()));
+            show(self.ui.hop_ticks.dyn_ref::<Element>().unwrap());
+        } else {
+            hide(self.ui.hop_ticks.dyn_ref::<Element>().unwrap());
         }
 
         if frame_duration_s > 0.000_001 {
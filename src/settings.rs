@@ -0,0 +1,111 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Serialize, Deserialize};
+use strafe_core::player::Kinematics;
+use web_sys::Storage;
+
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TickRate {
+    Tick64,
+    Tick66,
+    Tick100,
+    Tick128,
+    Fps125,
+    Fps250,
+    Fps333,
+}
+
+impl TickRate {
+    pub fn duration_s(self) -> f32 {
+        match self {
+            TickRate::Tick64  => 1.0 / 64.0,
+            // Source's "66 tick" is really a 15ms interval
+            TickRate::Tick66  => 0.015,
+            TickRate::Tick100 => 0.01,
+            TickRate::Tick128 => 1.0 / 128.0,
+            // Quake 3 simulates each frame with integer milliseconds
+            TickRate::Fps125  => 0.008,
+            TickRate::Fps250  => 0.004,
+            TickRate::Fps333  => 0.003,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct SimSettings {
+    pub tick_rate: TickRate,
+}
+
+impl SimSettings {
+    pub fn load(storage: &Storage, key: &str) -> Result<Self, ()> {
+        let data = storage.get_item(key)
+            .map_err(|_| ())?
+            .ok_or(())?;
+        serde_json::from_str(data.as_str()).map_err(|_| ())
+    }
+
+    pub fn save(&self, storage: &Storage, key: &str) -> Result<(), ()> {
+        let data = serde_json::to_string(&self).map_err(|_| ())?;
+        storage.set_item(key, data.as_str()).map_err(|_| ())
+    }
+}
+
+impl Default for SimSettings {
+    fn default() -> Self {
+        Self{
+            tick_rate: TickRate::Tick100,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MovementPreset {
+    pub name: String,
+    pub kinematics: Kinematics,
+}
+
+impl MovementPreset {
+    pub fn from_json(data: &str) -> Result<Self, ()> {
+        serde_json::from_str(data).map_err(|_| ())
+    }
+
+    pub fn to_json(&self) -> Result<String, ()> {
+        serde_json::to_string_pretty(&self).map_err(|_| ())
+    }
+}
+
+// the movement currently in use, and any presets the user has saved
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MovementSettings {
+    pub kinematics: Kinematics,
+    pub presets: Vec<MovementPreset>,
+}
+
+impl MovementSettings {
+    pub fn load(storage: &Storage, key: &str) -> Result<Self, ()> {
+        let data = storage.get_item(key)
+            .map_err(|_| ())?
+            .ok_or(())?;
+        serde_json::from_str(data.as_str()).map_err(|_| ())
+    }
+
+    pub fn save(&self, storage: &Storage, key: &str) -> Result<(), ()> {
+        let data = serde_json::to_string(&self).map_err(|_| ())?;
+        storage.set_item(key, data.as_str()).map_err(|_| ())
+    }
+}
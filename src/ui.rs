@@ -27,6 +27,7 @@ use web_sys::{
     HtmlElement,
     HtmlInputElement,
     HtmlSelectElement,
+    HtmlTextAreaElement,
     Window,
 };
 
@@ -47,6 +48,10 @@ pub struct UI {
     pub speed_ups: HtmlElement,
     pub speed_mph: HtmlElement,
     pub speed_kph: HtmlElement,
+    pub speed_delta: HtmlElement,
+    pub double_jump: HtmlElement,
+    pub hop_ticks: HtmlElement,
+    pub assist: HtmlElement,
     pub menu: HtmlDivElement,
     pub menu_continue: HtmlButtonElement,
     pub menu_tutorial: HtmlButtonElement,
@@ -60,29 +65,99 @@ pub struct UI {
     pub bind_back    : HtmlButtonElement,
     pub bind_right   : HtmlButtonElement,
     pub bind_jump    : HtmlButtonElement,
+    pub bind_crouch  : HtmlButtonElement,
+    pub bind_walk    : HtmlButtonElement,
+    pub bind_special : HtmlButtonElement,
+    pub bind_fire    : HtmlButtonElement,
     pub bind_interact: HtmlButtonElement,
     pub practice_options: HtmlElement,
     pub map_runway: HtmlButtonElement,
     pub map_freestyle: HtmlButtonElement,
+    pub compare: HtmlSelectElement,
+    pub weapon: HtmlSelectElement,
     pub move_vq3_like: HtmlButtonElement,
     pub move_qw_like: HtmlButtonElement,
     pub move_hybrid: HtmlButtonElement,
+    pub move_cpma_like: HtmlButtonElement,
+    pub move_source_like: HtmlButtonElement,
+    pub move_warsow_like: HtmlButtonElement,
+    pub preset_list: HtmlDivElement,
+    pub preset_name: HtmlInputElement,
+    pub preset_save: HtmlButtonElement,
+    pub preset_delete: HtmlButtonElement,
+    pub preset_json: HtmlTextAreaElement,
+    pub preset_export: HtmlButtonElement,
+    pub preset_import: HtmlButtonElement,
+    pub cfg_dialect: HtmlSelectElement,
+    pub cfg_file: HtmlInputElement,
+    pub cfg_text: HtmlTextAreaElement,
+    pub cfg_import: HtmlButtonElement,
+    pub cfg_warnings: HtmlDivElement,
+    pub tick_rate: HtmlSelectElement,
     pub move_gravity: HtmlInputElement,
     pub move_jump_impulse: HtmlInputElement,
+    pub move_ramp_jump: HtmlInputElement,
+    pub move_dj_enable: HtmlInputElement,
+    pub move_dj_time: HtmlInputElement,
+    pub move_dj_impulse: HtmlInputElement,
+    pub move_cap_mode: HtmlSelectElement,
+    pub move_cap_speed: HtmlInputElement,
+    pub move_cap_factor: HtmlInputElement,
     pub move_stall_speed: HtmlInputElement,
     pub move_friction: HtmlInputElement,
     pub move_ground_speed: HtmlInputElement,
     pub move_ground_accel: HtmlInputElement,
+    pub move_walk_speed: HtmlInputElement,
+    pub move_walk_accel: HtmlInputElement,
+    pub move_crouch_speed: HtmlInputElement,
+    pub move_crouch_accel: HtmlInputElement,
+    pub move_swim_speed: HtmlInputElement,
+    pub move_swim_accel: HtmlInputElement,
+    pub move_swim_fric: HtmlInputElement,
+    pub move_climb_speed: HtmlInputElement,
+    pub move_slide_enable: HtmlInputElement,
+    pub move_slide_fric: HtmlInputElement,
+    pub move_slide_time: HtmlInputElement,
+    pub move_dash_enable: HtmlInputElement,
+    pub move_dash_speed: HtmlInputElement,
+    pub move_dash_up: HtmlInputElement,
+    pub move_dash_time: HtmlInputElement,
+    pub move_wj_enable: HtmlInputElement,
+    pub move_wj_bounce: HtmlInputElement,
+    pub move_wj_speed: HtmlInputElement,
+    pub move_wj_up: HtmlInputElement,
+    pub move_wj_time: HtmlInputElement,
     pub move_air_speed: HtmlInputElement,
     pub move_air_accel: HtmlInputElement,
     pub move_turn_enabled: HtmlInputElement,
     pub move_turn_speed: HtmlInputElement,
     pub move_turn_accel: HtmlInputElement,
+    pub move_stop_enabled: HtmlInputElement,
+    pub move_stop_speed: HtmlInputElement,
+    pub move_stop_accel: HtmlInputElement,
+    pub move_ctrl_enabled: HtmlInputElement,
+    pub move_ctrl_value: HtmlInputElement,
+    pub move_integrator: HtmlSelectElement,
+    pub move_jump_mode: HtmlSelectElement,
+    pub move_jump_buffer: HtmlInputElement,
+    pub move_land_mode: HtmlSelectElement,
+    pub move_land_ticks: HtmlInputElement,
+    pub move_q3_fidelity: HtmlInputElement,
     pub menu_bot: HtmlElement,
     pub bot_mode: HtmlSelectElement,
+    pub bot_start: HtmlSelectElement,
+    pub bot_start_angle: HtmlInputElement,
+    pub bot_start_x: HtmlInputElement,
+    pub bot_skill: HtmlInputElement,
+    pub bot_seed: HtmlInputElement,
+    pub bot_flaw_aim: HtmlInputElement,
+    pub bot_flaw_keys: HtmlInputElement,
+    pub bot_flaw_jump: HtmlInputElement,
+    pub bot_flaw_turn: HtmlInputElement,
     pub bot_hop: HtmlInputElement,
     pub bot_move: HtmlInputElement,
     pub bot_turn: HtmlInputElement,
+    pub bot_assist: HtmlInputElement,
 }
 
 impl UI {
@@ -93,7 +168,11 @@ impl UI {
             KeyCode::KeyS  => &self.bind_back,
             KeyCode::KeyD  => &self.bind_right,
             KeyCode::KeyF  => &self.bind_interact,
+            KeyCode::KeyC  => &self.bind_crouch,
+            KeyCode::KeyE  => &self.bind_special,
+            KeyCode::Shift => &self.bind_walk,
             KeyCode::Space => &self.bind_jump,
+            KeyCode::MouseLeft => &self.bind_fire,
         }
     }
 }
@@ -128,6 +207,10 @@ pub fn get_ui() -> UI {
         speed_ups        : get_as::<HtmlElement      >(&document, "strafe_speed_ups"),
         speed_mph        : get_as::<HtmlElement      >(&document, "strafe_speed_mph"),
         speed_kph        : get_as::<HtmlElement      >(&document, "strafe_speed_kph"),
+        speed_delta      : get_as::<HtmlElement      >(&document, "strafe_speed_delta"),
+        double_jump      : get_as::<HtmlElement      >(&document, "strafe_double_jump"),
+        hop_ticks        : get_as::<HtmlElement      >(&document, "strafe_hop_ticks"),
+        assist           : get_as::<HtmlElement      >(&document, "strafe_assist"),
         menu             : get_as::<HtmlDivElement   >(&document, "strafe_menu"),
         menu_continue    : get_as::<HtmlButtonElement>(&document, "strafe_menu_continue"),
         menu_tutorial    : get_as::<HtmlButtonElement>(&document, "strafe_menu_tutorial"),
@@ -141,28 +224,98 @@ pub fn get_ui() -> UI {
         bind_back        : get_as::<HtmlButtonElement>(&document, "strafe_bind_back"),
         bind_right       : get_as::<HtmlButtonElement>(&document, "strafe_bind_right"),
         bind_jump        : get_as::<HtmlButtonElement>(&document, "strafe_bind_jump"),
+        bind_crouch      : get_as::<HtmlButtonElement>(&document, "strafe_bind_crouch"),
+        bind_walk        : get_as::<HtmlButtonElement>(&document, "strafe_bind_walk"),
+        bind_special     : get_as::<HtmlButtonElement>(&document, "strafe_bind_special"),
+        bind_fire        : get_as::<HtmlButtonElement>(&document, "strafe_bind_fire"),
         bind_interact    : get_as::<HtmlButtonElement>(&document, "strafe_bind_interact"),
         practice_options : get_as::<HtmlElement      >(&document, "strafe_practice_options"),
         map_runway       : get_as::<HtmlButtonElement>(&document, "strafe_map_runway"),
         map_freestyle    : get_as::<HtmlButtonElement>(&document, "strafe_map_freestyle"),
+        compare          : get_as::<HtmlSelectElement>(&document, "strafe_compare"),
+        weapon           : get_as::<HtmlSelectElement>(&document, "strafe_weapon"),
         move_vq3_like    : get_as::<HtmlButtonElement>(&document, "strafe_move_vq3-like"),
         move_qw_like     : get_as::<HtmlButtonElement>(&document, "strafe_move_qw-like"),
         move_hybrid      : get_as::<HtmlButtonElement>(&document, "strafe_move_hybrid"),
+        move_cpma_like   : get_as::<HtmlButtonElement>(&document, "strafe_move_cpma-like"),
+        move_source_like : get_as::<HtmlButtonElement>(&document, "strafe_move_source-like"),
+        move_warsow_like : get_as::<HtmlButtonElement>(&document, "strafe_move_warsow-like"),
+        preset_list      : get_as::<HtmlDivElement   >(&document, "strafe_move_user_presets"),
+        preset_name      : get_as::<HtmlInputElement >(&document, "strafe_preset_name"),
+        preset_save      : get_as::<HtmlButtonElement>(&document, "strafe_preset_save"),
+        preset_delete    : get_as::<HtmlButtonElement>(&document, "strafe_preset_delete"),
+        preset_json      : get_as::<HtmlTextAreaElement>(&document, "strafe_preset_json"),
+        preset_export    : get_as::<HtmlButtonElement>(&document, "strafe_preset_export"),
+        preset_import    : get_as::<HtmlButtonElement>(&document, "strafe_preset_import"),
+        cfg_dialect      : get_as::<HtmlSelectElement>(&document, "strafe_cfg_dialect"),
+        cfg_file         : get_as::<HtmlInputElement >(&document, "strafe_cfg_file"),
+        cfg_text         : get_as::<HtmlTextAreaElement>(&document, "strafe_cfg_text"),
+        cfg_import       : get_as::<HtmlButtonElement>(&document, "strafe_cfg_import"),
+        cfg_warnings     : get_as::<HtmlDivElement   >(&document, "strafe_cfg_warnings"),
+        tick_rate        : get_as::<HtmlSelectElement>(&document, "strafe_tick_rate"),
         move_gravity     : get_as::<HtmlInputElement >(&document, "strafe_move_gravity"),
         move_jump_impulse: get_as::<HtmlInputElement >(&document, "strafe_move_jump_impulse"),
+        move_ramp_jump   : get_as::<HtmlInputElement >(&document, "strafe_move_ramp_jump"),
+        move_dj_enable   : get_as::<HtmlInputElement >(&document, "strafe_move_dj_enable"),
+        move_dj_time     : get_as::<HtmlInputElement >(&document, "strafe_move_dj_time"),
+        move_dj_impulse  : get_as::<HtmlInputElement >(&document, "strafe_move_dj_impulse"),
+        move_cap_mode    : get_as::<HtmlSelectElement>(&document, "strafe_move_cap_mode"),
+        move_cap_speed   : get_as::<HtmlInputElement >(&document, "strafe_move_cap_speed"),
+        move_cap_factor  : get_as::<HtmlInputElement >(&document, "strafe_move_cap_factor"),
         move_stall_speed : get_as::<HtmlInputElement >(&document, "strafe_move_stall_speed"),
         move_friction    : get_as::<HtmlInputElement >(&document, "strafe_move_friction"),
         move_ground_speed: get_as::<HtmlInputElement >(&document, "strafe_move_ground_speed"),
         move_ground_accel: get_as::<HtmlInputElement >(&document, "strafe_move_ground_accel"),
+        move_walk_speed  : get_as::<HtmlInputElement >(&document, "strafe_move_walk_speed"),
+        move_walk_accel  : get_as::<HtmlInputElement >(&document, "strafe_move_walk_accel"),
+        move_crouch_speed: get_as::<HtmlInputElement >(&document, "strafe_move_crouch_speed"),
+        move_crouch_accel: get_as::<HtmlInputElement >(&document, "strafe_move_crouch_accel"),
+        move_swim_speed  : get_as::<HtmlInputElement >(&document, "strafe_move_swim_speed"),
+        move_swim_accel  : get_as::<HtmlInputElement >(&document, "strafe_move_swim_accel"),
+        move_swim_fric   : get_as::<HtmlInputElement >(&document, "strafe_move_swim_fric"),
+        move_climb_speed : get_as::<HtmlInputElement >(&document, "strafe_move_climb_speed"),
+        move_slide_enable: get_as::<HtmlInputElement >(&document, "strafe_move_slide_enable"),
+        move_slide_fric  : get_as::<HtmlInputElement >(&document, "strafe_move_slide_fric"),
+        move_slide_time  : get_as::<HtmlInputElement >(&document, "strafe_move_slide_time"),
+        move_dash_enable : get_as::<HtmlInputElement >(&document, "strafe_move_dash_enable"),
+        move_dash_speed  : get_as::<HtmlInputElement >(&document, "strafe_move_dash_speed"),
+        move_dash_up     : get_as::<HtmlInputElement >(&document, "strafe_move_dash_up"),
+        move_dash_time   : get_as::<HtmlInputElement >(&document, "strafe_move_dash_time"),
+        move_wj_enable   : get_as::<HtmlInputElement >(&document, "strafe_move_wj_enable"),
+        move_wj_bounce   : get_as::<HtmlInputElement >(&document, "strafe_move_wj_bounce"),
+        move_wj_speed    : get_as::<HtmlInputElement >(&document, "strafe_move_wj_speed"),
+        move_wj_up       : get_as::<HtmlInputElement >(&document, "strafe_move_wj_up"),
+        move_wj_time     : get_as::<HtmlInputElement >(&document, "strafe_move_wj_time"),
         move_air_speed   : get_as::<HtmlInputElement >(&document, "strafe_move_air_speed"),
         move_air_accel   : get_as::<HtmlInputElement >(&document, "strafe_move_air_accel"),
         move_turn_enabled: get_as::<HtmlInputElement >(&document, "strafe_move_turn_enabled"),
         move_turn_speed  : get_as::<HtmlInputElement >(&document, "strafe_move_turn_speed"),
         move_turn_accel  : get_as::<HtmlInputElement >(&document, "strafe_move_turn_accel"),
+        move_stop_enabled: get_as::<HtmlInputElement >(&document, "strafe_move_stop_enabled"),
+        move_stop_speed  : get_as::<HtmlInputElement >(&document, "strafe_move_stop_speed"),
+        move_stop_accel  : get_as::<HtmlInputElement >(&document, "strafe_move_stop_accel"),
+        move_ctrl_enabled: get_as::<HtmlInputElement >(&document, "strafe_move_ctrl_enabled"),
+        move_ctrl_value  : get_as::<HtmlInputElement >(&document, "strafe_move_ctrl_value"),
+        move_integrator  : get_as::<HtmlSelectElement>(&document, "strafe_move_integrator"),
+        move_jump_mode   : get_as::<HtmlSelectElement>(&document, "strafe_move_jump_mode"),
+        move_jump_buffer : get_as::<HtmlInputElement >(&document, "strafe_move_jump_buffer"),
+        move_land_mode   : get_as::<HtmlSelectElement>(&document, "strafe_move_land_mode"),
+        move_land_ticks  : get_as::<HtmlInputElement >(&document, "strafe_move_land_ticks"),
+        move_q3_fidelity : get_as::<HtmlInputElement >(&document, "strafe_move_q3_fidelity"),
         menu_bot         : get_as::<HtmlElement      >(&document, "strafe_menu_bot"),
         bot_mode         : get_as::<HtmlSelectElement>(&document, "strafe_bot_mode"),
+        bot_start        : get_as::<HtmlSelectElement>(&document, "strafe_bot_start"),
+        bot_start_angle  : get_as::<HtmlInputElement >(&document, "strafe_bot_start_angle"),
+        bot_start_x      : get_as::<HtmlInputElement >(&document, "strafe_bot_start_x"),
+        bot_skill        : get_as::<HtmlInputElement >(&document, "strafe_bot_skill"),
+        bot_seed         : get_as::<HtmlInputElement >(&document, "strafe_bot_seed"),
+        bot_flaw_aim     : get_as::<HtmlInputElement >(&document, "strafe_bot_flaw_aim"),
+        bot_flaw_keys    : get_as::<HtmlInputElement >(&document, "strafe_bot_flaw_keys"),
+        bot_flaw_jump    : get_as::<HtmlInputElement >(&document, "strafe_bot_flaw_jump"),
+        bot_flaw_turn    : get_as::<HtmlInputElement >(&document, "strafe_bot_flaw_turn"),
         bot_hop          : get_as::<HtmlInputElement >(&document, "strafe_bot_hop"),
         bot_move         : get_as::<HtmlInputElement >(&document, "strafe_bot_move"),
         bot_turn         : get_as::<HtmlInputElement >(&document, "strafe_bot_turn"),
+        bot_assist       : get_as::<HtmlInputElement >(&document, "strafe_bot_assist"),
     }
 }
\ No newline at end of file
@@ -0,0 +1,116 @@
+/*
+ * Copyright 2019 Michael Lodato <zvxryb@gmail.com>
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+
+extern crate serde_json;
+extern crate strafe_core;
+
+use strafe_core::fit::{fit, parse_csv, trace_error, PARAMS};
+use strafe_core::player::{
+    Kinematics,
+    MOVE_CPMA_LIKE,
+    MOVE_HYBRID,
+    MOVE_QW_LIKE,
+    MOVE_SOURCE_LIKE,
+    MOVE_VQ3_LIKE,
+    MOVE_WARSOW_LIKE,
+};
+
+use std::env;
+use std::fs;
+use std::process;
+
+const USAGE: &str = "\
+usage: fit <trace.csv> [--base <preset>] [--tick-rate <hz>] [--name <name>] [--iterations <n>]
+
+Fits gravity, friction and acceleration to a speed trace, starting from a base
+preset (vq3-like, qw-like, hybrid, cpma-like, source-like or warsow-like), and
+prints a preset which can be imported into the trainer.
+
+The trace is a CSV with a header naming its columns: time (seconds), speed, yaw
+(degrees) and, optionally, forward, back, left, right, jump, crouch and walk,
+which are 0 or 1.  Inputs are held until the next sample, so traces should be
+sampled at least once per tick.";
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn base_preset(name: &str) -> Option<Kinematics> {
+    match name {
+        "vq3-like"    => Some(MOVE_VQ3_LIKE),
+        "qw-like"     => Some(MOVE_QW_LIKE),
+        "hybrid"      => Some(MOVE_HYBRID),
+        "cpma-like"   => Some(MOVE_CPMA_LIKE),
+        "source-like" => Some(MOVE_SOURCE_LIKE),
+        "warsow-like" => Some(MOVE_WARSOW_LIKE),
+        _ => None,
+    }
+}
+
+fn main() {
+    let mut path = None;
+    let mut base = MOVE_VQ3_LIKE;
+    let mut tick_rate = 125.0;
+    let mut name = "Fitted".to_string();
+    let mut iterations = 1000;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| exit_with(USAGE));
+        match arg.as_str() {
+            "--base" => {
+                base = base_preset(value().as_str())
+                    .unwrap_or_else(|| exit_with("unknown base preset"));
+            }
+            "--tick-rate" => {
+                tick_rate = value().parse::<f32>()
+                    .ok()
+                    .filter(|&tick_rate| tick_rate > 0.0)
+                    .unwrap_or_else(|| exit_with("invalid tick rate"));
+            }
+            "--name" => name = value(),
+            "--iterations" => {
+                iterations = value().parse::<usize>()
+                    .unwrap_or_else(|_| exit_with("invalid iteration count"));
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => exit_with(USAGE),
+        }
+    }
+    let path = path.unwrap_or_else(|| exit_with(USAGE));
+
+    let data = fs::read_to_string(&path)
+        .unwrap_or_else(|err| exit_with(format!("failed to read {}: {}", path, err).as_str()));
+    let samples = parse_csv(data.as_str())
+        .unwrap_or_else(|err| exit_with(format!("failed to parse {}: {}", path, err).as_str()));
+
+    let tick_s = 1.0 / tick_rate;
+    eprintln!("base error: {:.2}ups", trace_error(&base, &samples, tick_s));
+    let (kinematics, error) = fit(&base, &samples, tick_s, iterations);
+    eprintln!("fit error: {:.2}ups", error);
+    for param in PARAMS {
+        eprintln!("{}: {} -> {}", param.name, param.get(&base), param.get(&kinematics));
+    }
+
+    let preset = serde_json::json!({
+        "name": name,
+        "kinematics": kinematics,
+    });
+    println!("{}", serde_json::to_string_pretty(&preset).expect("failed to serialize preset"));
+}